@@ -0,0 +1,57 @@
+//! Backtraces of the error sets, generated with `backtrace` option
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, Type};
+
+/// Returns the `backtrace` method of the enum, delegating to the active variant. Error types with the backtrace,
+/// like `error!` with `backtrace` marker and other sets, define the inherent method `__backtrace`,
+/// which takes priority over the fallback trait returning `None`.
+pub(crate) fn enum_backtrace(enum_name: &Ident, generics: &Generics, err_types: &[(Ident, &Type)]) -> TokenStream {
+    let arms = err_types.iter().map(|(ty_name, _)| {
+        quote! {
+            Self::#ty_name(ref inner) => inner.__backtrace(),
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #enum_name #ty_generics #where_clause {
+            /// Returns the backtrace of the error held by the variant, if it has one and it was captured
+            pub fn backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                trait NoBacktrace {
+                    fn __backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                        ::core::option::Option::None
+                    }
+                }
+                impl<T: ?Sized> NoBacktrace for T {}
+
+                match *self {
+                    #(#arms)*
+                }
+            }
+
+            #[doc(hidden)]
+            pub fn __backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                self.backtrace()
+            }
+        }
+    }
+}
+
+/// Returns the `backtrace` method of the traced set, delegating to its kind
+pub(crate) fn traced_backtrace(name: &Ident, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Returns the backtrace of the error held by the kind, if it has one and it was captured
+            pub fn backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                self.kind.backtrace()
+            }
+
+            #[doc(hidden)]
+            pub fn __backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                self.kind.backtrace()
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Documentation of the generated enums and the processed items
+
+use crate::{generics, inline::InlineError};
+use quote::quote;
+use syn::{Attribute, Generics, Ident, Type};
+
+/// Returns the path of the type without generic arguments, like `io::Error` for `io::Error`
+/// or `Wrapper` for `Wrapper<T>`
+fn type_path(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Returns the `# Errors` section, listing variants of the enum with links to the error types.
+/// Errors defined inline are listed along with their message templates.
+pub(crate) fn errors_section(
+    variants: &[(Ident, &Type)],
+    generics: &Generics,
+    inline_errors: &[InlineError],
+) -> Vec<Attribute> {
+    let mut lines = vec![String::new(), "# Errors".to_owned(), String::new()];
+    for (name, ty) in variants {
+        let Some(path) = crate::error_path(ty) else {
+            continue;
+        };
+        // types depending on generic parameters can not be linked
+        let mut line = if generics::depends_on_params(ty, generics) {
+            format!("- `{}`", type_path(path))
+        } else {
+            format!("- [`{name}`]({})", type_path(path))
+        };
+        let type_name = &path.segments.last().unwrap().ident;
+        if let Some(error) = inline_errors.iter().find(|error| error.name == *type_name) {
+            line.push_str(&format!(": `{}`", error.message.value()));
+        }
+        lines.push(line);
+    }
+
+    lines
+        .into_iter()
+        .map(|line| syn::parse_quote!(#[doc = #line]))
+        .collect()
+}
+
+/// Returns the doc comment of the generated enum, which links back to the item
+pub(crate) fn enum_doc(description: &str, item_name: &str, item_link: &str) -> syn::Meta {
+    let doc = format!("{description} [`{item_name}`]({item_link}).");
+    syn::parse2(quote! { doc = #doc }).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_section() {
+        let mut err_types: syn::punctuated::Punctuated<Type, syn::Token![,]> = syn::parse_quote!(
+            error!(InvalidPort<ParseIntError> { pub data: String } => "Invalid port [data={data}]"),
+            io::Error,
+        );
+        let inline_errors = crate::inline::extract(&mut err_types, Some(&syn::parse_quote!(errors))).unwrap();
+        let variants = crate::enum_variants(&err_types, &Generics::default());
+        let lines = errors_section(&variants, &Generics::default(), &inline_errors)
+            .into_iter()
+            .map(|attr| match attr.meta {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(line), .. }),
+                    ..
+                }) => line.value(),
+                other => panic!("Expected the doc attribute, got `{}`", quote!(#other)),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "",
+                "# Errors",
+                "",
+                "- [`InvalidPort`](errors::InvalidPort): `Invalid port [data={data}]`",
+                "- [`Error`](io::Error)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Detection of generic parameters used by the error types
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::HashSet;
+use syn::{
+    visit::{self, Visit},
+    GenericParam, Generics, Lifetime, Result, Type, TypePath, TypeReference, WherePredicate,
+};
+
+/// Collects names of all identifiers and lifetimes of the tokens, including nested groups
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<String>) {
+    let mut lifetime = false;
+    for token in tokens {
+        match &token {
+            TokenTree::Ident(ident) if lifetime => {
+                idents.insert(format!("'{ident}"));
+            }
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+        lifetime = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+    }
+}
+
+fn idents_of(item: &impl ToTokens) -> HashSet<String> {
+    let mut idents = HashSet::new();
+    collect_idents(item.to_token_stream(), &mut idents);
+    idents
+}
+
+fn param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+        GenericParam::Lifetime(param) => param.lifetime.to_string(),
+    }
+}
+
+/// Looks for elided lifetimes, like `&str` or `SyntaxError<'_>`
+struct ElidedLifetimes(Vec<syn::Error>);
+
+impl<'ast> Visit<'ast> for ElidedLifetimes {
+    fn visit_type_reference(&mut self, reference: &'ast TypeReference) {
+        if reference.lifetime.is_none() {
+            self.0.push(syn::Error::new_spanned(
+                reference,
+                "Elided lifetimes are not supported in error types, use the named lifetime of the function, like `&'a Error1`",
+            ));
+        }
+        visit::visit_type_reference(self, reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+        if lifetime.ident == "_" {
+            self.0.push(syn::Error::new_spanned(
+                lifetime,
+                "Elided lifetimes are not supported in error types, use the named lifetime of the function, like `SyntaxError<'a>`",
+            ));
+        }
+    }
+
+    // lifetimes of trait objects and bounds of `impl Trait` are not the part of the error type
+    fn visit_type_param_bound(&mut self, _: &'ast syn::TypeParamBound) {}
+}
+
+/// Returns an error for each elided lifetime of the error types
+fn check_elided_lifetimes(err_types: &[&Type]) -> Result<()> {
+    let mut visitor = ElidedLifetimes(Vec::new());
+    for ty in err_types {
+        visitor.visit_type(ty);
+    }
+    match visitor.0.into_iter().reduce(|mut errors, error| {
+        errors.combine(error);
+        errors
+    }) {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// Returns generics of the enum: parameters of the function and its impl block, which are used by the error types,
+/// along with their bounds and where-predicates.
+pub(crate) fn enum_generics(
+    err_types: &[&Type],
+    fn_generics: &Generics,
+    impl_generics: Option<&Generics>,
+) -> Result<Generics> {
+    check_elided_lifetimes(err_types)?;
+
+    let params = impl_generics
+        .into_iter()
+        .chain([fn_generics])
+        .flat_map(|generics| generics.params.iter())
+        .collect::<Vec<_>>();
+    let predicates = impl_generics
+        .into_iter()
+        .chain([fn_generics])
+        .filter_map(|generics| generics.where_clause.as_ref())
+        .flat_map(|where_clause| where_clause.predicates.iter())
+        .collect::<Vec<_>>();
+    let names = params.iter().map(|param| param_name(param)).collect::<HashSet<_>>();
+
+    // parameters used by the error types directly
+    let mut field_params = HashSet::new();
+    for ty in err_types {
+        field_params.extend(idents_of(ty).intersection(&names).cloned());
+    }
+
+    // parameters required by bounds of the used parameters
+    let mut used = field_params.clone();
+    loop {
+        let mut required = HashSet::new();
+        for param in &params {
+            if used.contains(&param_name(param)) {
+                required.extend(idents_of(param).intersection(&names).cloned());
+            }
+        }
+        for predicate in &predicates {
+            let bounded = match predicate {
+                WherePredicate::Type(predicate) => idents_of(&predicate.bounded_ty),
+                WherePredicate::Lifetime(predicate) => idents_of(&predicate.lifetime),
+                _ => HashSet::new(),
+            };
+            if !bounded.is_disjoint(&used) {
+                required.extend(idents_of(predicate).intersection(&names).cloned());
+            }
+        }
+        if required.is_subset(&used) {
+            break;
+        }
+        used.extend(required);
+    }
+
+    // lifetimes must be declared before other parameters
+    let (lifetimes, others): (Vec<_>, Vec<_>) = params
+        .into_iter()
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+
+    let mut generics = Generics::default();
+    for param in lifetimes.into_iter().chain(others) {
+        let name = param_name(param);
+        if !used.contains(&name) {
+            continue;
+        }
+        if !field_params.contains(&name) {
+            return Err(syn::Error::new_spanned(
+                param,
+                format!("Generic parameter `{name}` is required by bounds of the error types, but is not used by them"),
+            ));
+        }
+
+        // defaults are not allowed for the enum generated for the function
+        let mut param = param.clone();
+        match &mut param {
+            GenericParam::Type(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Const(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+        generics.params.push(param);
+    }
+
+    for predicate in predicates {
+        let idents = idents_of(predicate);
+        let mentioned = idents.intersection(&names).collect::<HashSet<_>>();
+        if !mentioned.is_empty() && mentioned.iter().all(|name| used.contains(*name)) {
+            generics.make_where_clause().predicates.push(predicate.clone());
+        }
+    }
+
+    Ok(generics)
+}
+
+/// Returns true if the type mentions any of the generic parameters, like `T`, `Wrapper<T>` or `SyntaxError<'a>`
+pub(crate) fn mentions_params(ty: &Type, generics: &Generics) -> bool {
+    let idents = idents_of(ty);
+    generics.params.iter().any(|param| idents.contains(&param_name(param)))
+}
+
+/// Returns true if the type is a generic parameter itself or its projection, like `T` or `S::Error`,
+/// or the reference to them, like `&'a T`
+pub(crate) fn depends_on_params(ty: &Type, generics: &Generics) -> bool {
+    match ty {
+        Type::Reference(reference) => depends_on_params(&reference.elem, generics),
+        Type::Paren(paren) => depends_on_params(&paren.elem, generics),
+        Type::Group(group) => depends_on_params(&group.elem, generics),
+        Type::Path(TypePath { qself: Some(_), .. }) => true,
+        Type::Path(TypePath { qself: None, path }) => {
+            let first = &path.segments.first().unwrap().ident;
+            path.leading_colon.is_none() && generics.type_params().any(|param| param.ident == *first)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,244 @@
+//! Inference of the error types from the function body
+
+use proc_macro2::Span;
+use quote::{quote, ToTokens};
+use std::collections::{HashMap, HashSet};
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    Block, Expr, ExprCall, ExprMethodCall, ExprTry, Ident, Result, Type,
+};
+
+/// Error types of the functions and methods processed along with the inferred one, so `?` applied to their calls
+/// is resolved to their enums
+#[derive(Default)]
+pub(crate) struct Callees {
+    /// Functions of the module, called like `read(..)?`
+    pub(crate) functions: HashMap<String, Type>,
+    /// Methods of the impl block, called like `self.read(..)?` or `Self::read(..)?`
+    pub(crate) methods: HashMap<String, Type>,
+}
+
+/// Methods of `Result`, `Option` and `Throwable`, which take the closure constructing the error
+const ERROR_MAPPERS: [&str; 3] = ["throw", "raise", "map_err"];
+
+/// Returns true if the identifier starts with the uppercase letter, so it is a type or a variant
+fn is_capitalized(ident: &syn::Ident) -> bool {
+    ident.to_string().starts_with(|c: char| c.is_uppercase())
+}
+
+/// Returns the type of the constructor path: `Error1` for `Error1`, `Error1::new` or `Kind::Variant`
+fn type_of_path(path: &syn::Path) -> Option<Type> {
+    let mut path = path.clone();
+    let last = &path.segments.last()?.ident;
+    let len = path.segments.len();
+    let parent_capitalized = len > 1 && is_capitalized(&path.segments[len - 2].ident);
+    // variant of the enum or the associated function, like `Kind::Variant` or `Error1::new`
+    if parent_capitalized {
+        path.segments.pop();
+        path.segments.pop_punct();
+    } else if !is_capitalized(last) {
+        return None;
+    }
+    Some(Type::Path(syn::TypePath { qself: None, path }))
+}
+
+/// Returns the error type constructed by the expression, like `Error1 { .. }`, `Error1(..)` or `Error1.into()`
+fn constructed_type(expr: &Expr) -> Option<Type> {
+    match expr {
+        Expr::MethodCall(call) if call.method == "into" && call.args.is_empty() => constructed_type(&call.receiver),
+        Expr::Paren(paren) => constructed_type(&paren.expr),
+        Expr::Block(block) => match block.block.stmts.last()? {
+            syn::Stmt::Expr(expr, None) => constructed_type(expr),
+            _ => None,
+        },
+        Expr::Struct(expr) if expr.qself.is_none() => type_of_path(&expr.path),
+        Expr::Call(ExprCall { func, .. }) => match &**func {
+            Expr::Path(path) if path.qself.is_none() => type_of_path(&path.path),
+            _ => None,
+        },
+        Expr::Path(path) if path.qself.is_none() => type_of_path(&path.path),
+        _ => None,
+    }
+}
+
+/// Collects the error types constructed by the function body and `?` applied to unknown expressions
+struct Inference<'a> {
+    callees: &'a Callees,
+    inferred: Vec<Type>,
+    uncertain: Vec<Span>,
+    /// Errors produced by the body, which are not recognized, like `Err(error)` or `.map_err(Into::into)`,
+    /// along with the description of the expression
+    opaque: Vec<(Span, String)>,
+}
+
+impl Inference<'_> {
+    fn push(&mut self, ty: Type) {
+        let key = ty.to_token_stream().to_string();
+        if !self
+            .inferred
+            .iter()
+            .any(|known| known.to_token_stream().to_string() == key)
+        {
+            self.inferred.push(ty);
+        }
+    }
+
+    /// Returns true if the expression constructs the error itself, like `Err(..)` or `.map_err(..)`,
+    /// so the unrecognized error is reported by the expression rather than by `?`
+    fn constructs_error(expr: &Expr) -> bool {
+        match expr {
+            Expr::Paren(paren) => Self::constructs_error(&paren.expr),
+            Expr::Await(expr) => Self::constructs_error(&expr.base),
+            Expr::MethodCall(call) => ERROR_MAPPERS.iter().any(|name| call.method == name),
+            Expr::Call(ExprCall { func, .. }) => matches!(&**func, Expr::Path(path) if path.path.is_ident("Err")),
+            _ => false,
+        }
+    }
+
+    /// Returns the error type of the expression `?` is applied to, if it's known
+    fn try_source(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Paren(paren) => self.try_source(&paren.expr),
+            Expr::Await(expr) => self.try_source(&expr.base),
+            Expr::MethodCall(call) if ERROR_MAPPERS.iter().any(|name| call.method == name) => match call.args.first() {
+                Some(Expr::Closure(closure)) => constructed_type(&closure.body),
+                _ => None,
+            },
+            Expr::MethodCall(call) => match &*call.receiver {
+                Expr::Path(receiver) if receiver.path.is_ident("self") => {
+                    self.callees.methods.get(&call.method.to_string()).cloned()
+                }
+                _ => None,
+            },
+            Expr::Call(ExprCall { func, args, .. }) => {
+                let Expr::Path(path) = &**func else {
+                    return None;
+                };
+                let segments = &path.path.segments;
+                if path.path.is_ident("Err") {
+                    args.first().and_then(constructed_type)
+                } else if path.qself.is_none() && segments.len() == 1 {
+                    self.callees.functions.get(&segments[0].ident.to_string()).cloned()
+                } else if path.qself.is_none() && segments.len() == 2 && segments[0].ident == "Self" {
+                    self.callees.methods.get(&segments[1].ident.to_string()).cloned()
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Inference<'_> {
+    fn visit_expr_try(&mut self, expr: &'ast ExprTry) {
+        match self.try_source(&expr.expr) {
+            Some(ty) => self.push(ty),
+            None if Self::constructs_error(&expr.expr) => {}
+            None => self.uncertain.push(expr.question_token.span()),
+        }
+        visit::visit_expr_try(self, expr);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if matches!(&*call.func, Expr::Path(path) if path.path.is_ident("Err")) {
+            match call.args.first().and_then(constructed_type) {
+                Some(ty) => self.push(ty),
+                None => {
+                    let span = call.args.first().map_or_else(|| call.span(), Spanned::span);
+                    self.opaque.push((span, "`Err(..)`".to_owned()));
+                }
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if ERROR_MAPPERS.iter().any(|name| call.method == name) {
+            let constructed = match call.args.first() {
+                Some(Expr::Closure(closure)) => constructed_type(&closure.body),
+                _ => None,
+            };
+            match constructed {
+                Some(ty) => self.push(ty),
+                None => {
+                    let span = call.args.first().map_or_else(|| call.method.span(), Spanned::span);
+                    self.opaque.push((span, format!("the closure of `{}`", call.method)));
+                }
+            }
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    // closures, async blocks and nested items return errors of their own
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+fn scan<'a>(block: &Block, callees: &'a Callees) -> Inference<'a> {
+    let mut inference = Inference {
+        callees,
+        inferred: Vec::new(),
+        uncertain: Vec::new(),
+        opaque: Vec::new(),
+    };
+    inference.visit_block(block);
+    inference
+}
+
+/// Returns the error types constructed by the function body. If the error type of some `?`, `Err(..)`
+/// or the closure of `map_err` is unknown, returns an error pointing to it and listing the types inferred so far.
+pub(crate) fn infer_errors(block: &Block, callees: &Callees) -> Result<Vec<Type>> {
+    let inference = scan(block, callees);
+    let inferred = &inference.inferred;
+    let message = |what: &str| {
+        format!(
+            "Can not infer the error type of {what}, inferred so far: `({})`. \
+             List the error types explicitly, like `Result<T, (Error1, Error2)>`",
+            quote!(#(#inferred),*)
+        )
+    };
+    let error = inference
+        .uncertain
+        .iter()
+        .map(|span| (*span, "`?`"))
+        .chain(inference.opaque.iter().map(|(span, what)| (*span, what.as_str())))
+        .map(|(span, what)| syn::Error::new(span, message(what)))
+        .reduce(|mut errors, error| {
+            errors.combine(error);
+            errors
+        });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(inference.inferred),
+    }
+}
+
+/// Returns the name of the type path, like `Error1` for `errors::Error1`
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns variants of the enum, which are never produced by the function body. Error types are matched by name,
+/// so `errors::Error1` and `Error1` are the same. Nothing is returned if the body produces errors which are not recognized,
+/// since they may be of any type.
+pub(crate) fn unused_variants<'a, 'b>(
+    block: &Block,
+    callees: &Callees,
+    variants: &'a [(Ident, &'b Type)],
+) -> Vec<&'a (Ident, &'b Type)> {
+    let inference = scan(block, callees);
+    if !inference.opaque.is_empty() || !inference.uncertain.is_empty() {
+        return Vec::new();
+    }
+    let produced = inference.inferred.iter().filter_map(type_name).collect::<HashSet<_>>();
+    variants
+        .iter()
+        .filter(|(_, ty)| type_name(ty).is_some_and(|name| !produced.contains(&name)))
+        .collect()
+}
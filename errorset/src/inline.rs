@@ -0,0 +1,247 @@
+//! Inline error definitions of the tuple, like `error!(NotFound { id: u32 } => "Not found [id={id}]")`
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Field, Ident, LitStr, Result, Token, Type, TypeMacro, Visibility,
+};
+
+/// Error struct defined inside the tuple, the same way `smarterr::error!` defines it
+pub(crate) struct InlineError {
+    pub(crate) name: Ident,
+    source: Option<Type>,
+    fields: Punctuated<Field, Token![,]>,
+    /// Markers in brackets, like `[location]`, adding fields filled by the constructor
+    markers: Vec<Marker>,
+    pub(crate) message: LitStr,
+}
+
+/// Field added to the error by the marker
+enum Marker {
+    /// Location of the code, which created the error
+    Location,
+    /// Backtrace captured when the error is created
+    Backtrace,
+}
+
+impl Marker {
+    fn name(&self) -> Ident {
+        let name = match self {
+            Marker::Location => "location",
+            Marker::Backtrace => "backtrace",
+        };
+        Ident::new(name, Span::call_site())
+    }
+
+    fn field(&self) -> TokenStream {
+        match self {
+            Marker::Location => quote! { pub location: &'static ::core::panic::Location<'static> },
+            Marker::Backtrace => quote! { pub backtrace: ::std::backtrace::Backtrace },
+        }
+    }
+
+    fn init(&self) -> TokenStream {
+        match self {
+            Marker::Location => quote! { location: ::core::panic::Location::caller() },
+            Marker::Backtrace => quote! { backtrace: ::std::backtrace::Backtrace::capture() },
+        }
+    }
+
+    /// Returns the method of the error, which is found by the enums with `backtrace` option
+    fn method(&self) -> Option<TokenStream> {
+        match self {
+            Marker::Location => None,
+            Marker::Backtrace => Some(quote! {
+                #[doc(hidden)]
+                pub fn __backtrace(&self) -> ::core::option::Option<&::std::backtrace::Backtrace> {
+                    // disabled backtraces are not reported, so `RUST_BACKTRACE` is respected
+                    match self.backtrace.status() {
+                        ::std::backtrace::BacktraceStatus::Captured => ::core::option::Option::Some(&self.backtrace),
+                        _ => ::core::option::Option::None,
+                    }
+                }
+            }),
+        }
+    }
+}
+
+impl Parse for Marker {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let marker = input.parse::<Ident>()?;
+        match marker.to_string().as_str() {
+            "location" => Ok(Marker::Location),
+            "backtrace" => Ok(Marker::Backtrace),
+            _ => Err(syn::Error::new_spanned(marker, "Unknown marker of the error")),
+        }
+    }
+}
+
+impl Parse for InlineError {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        let source = if input.peek(Token![<]) {
+            input.parse::<Token![<]>()?;
+            let source = input.parse()?;
+            input.parse::<Token![>]>()?;
+            Some(source)
+        } else {
+            None
+        };
+        let markers = if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            Punctuated::<Marker, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let content;
+        syn::braced!(content in input);
+        let fields = content.parse_terminated(Field::parse_named, Token![,])?;
+        input.parse::<Token![=>]>()?;
+        let message = input.parse()?;
+        Ok(InlineError { name, source, fields, markers, message })
+    }
+}
+
+impl InlineError {
+    /// Returns the definition of the struct along with its `Display` and `Error` implementations
+    pub(crate) fn definition(&self, vis: &Visibility) -> TokenStream {
+        let InlineError { name, source, fields, markers, message } = self;
+        let doc = format!("Error with the message `{}`.", message.value());
+        // the message may refer to the source and the fields of the markers as well
+        let field_names = fields
+            .iter()
+            .map(|field| field.ident.clone().unwrap())
+            .chain(markers.iter().map(Marker::name))
+            .chain(source.iter().map(|_| Ident::new("source", Span::call_site())))
+            .collect::<Vec<_>>();
+        let constructor = self.constructor(vis);
+        let marker_fields = markers.iter().map(Marker::field);
+        // fields without visibility get the visibility of the struct, so the function can construct the error
+        let fields = fields
+            .iter()
+            .map(|field| match field.vis {
+                Visibility::Inherited => Field { vis: vis.clone(), ..field.clone() },
+                _ => field.clone(),
+            })
+            .map(|field| quote! { #field })
+            .chain(marker_fields);
+
+        // `thiserror` implements `provide` for the backtrace, which requires nightly, so such errors implement
+        // `Display` and `Error` directly
+        let has_backtrace = markers.iter().any(|marker| matches!(marker, Marker::Backtrace));
+        if cfg!(feature = "thiserror") && !has_backtrace {
+            let source = source.iter();
+            return quote! {
+                #[doc = #doc]
+                #[derive(::thiserror::Error, Debug)]
+                #[error(#message)]
+                #vis struct #name {
+                    #(#fields,)*
+                    #(
+                        #[source]
+                        pub source: #source,
+                    )*
+                }
+
+                #constructor
+            };
+        }
+
+        let source_fn = source.as_ref().map(|_| {
+            let as_dyn_error = crate::variant::as_dyn_error();
+            quote! {
+                fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                    #as_dyn_error
+                    ::core::option::Option::Some(self.source.__as_dyn_error())
+                }
+            }
+        });
+        let source_field = source.iter();
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug)]
+            #vis struct #name {
+                #(#fields,)*
+                #(pub source: #source_field,)*
+            }
+
+            impl ::core::fmt::Display for #name {
+                #[allow(unused_variables)]
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let Self { #(#field_names,)* } = self;
+                    ::core::write!(f, #message)
+                }
+            }
+
+            impl ::core::error::Error for #name {
+                #source_fn
+            }
+
+            #constructor
+        }
+    }
+
+    /// Returns the constructor `new` filling fields of the markers, if there are any
+    fn constructor(&self, vis: &Visibility) -> Option<TokenStream> {
+        if self.markers.is_empty() {
+            return None;
+        }
+        let name = &self.name;
+        let params = self.fields.iter().map(|field| {
+            let (ident, ty) = (&field.ident, &field.ty);
+            quote! { #ident: #ty }
+        });
+        let field_names = self.fields.iter().map(|field| &field.ident);
+        let source_param = self.source.iter().map(|source| quote! { source: #source });
+        let source_init = self.source.iter().map(|_| quote! { source });
+        let inits = self.markers.iter().map(Marker::init);
+        let methods = self.markers.iter().filter_map(Marker::method);
+        Some(quote! {
+            impl #name {
+                /// Creates the error, filling fields of the markers
+                #[track_caller]
+                #[allow(clippy::too_many_arguments)]
+                #vis fn new(#(#params,)* #(#source_param)*) -> Self {
+                    #name { #(#field_names,)* #(#source_init,)* #(#inits,)* }
+                }
+
+                #(#methods)*
+            }
+        })
+    }
+}
+
+/// Replaces inline definitions of the tuple with paths to the structs, placed into the module if it's defined.
+/// Returns the definitions found.
+pub(crate) fn extract<'a>(
+    err_types: impl IntoIterator<Item = &'a mut Type>,
+    module: Option<&Ident>,
+) -> Result<Vec<InlineError>> {
+    let mut errors = Vec::new();
+    for ty in err_types {
+        let Type::Macro(TypeMacro { mac }) = ty else {
+            continue;
+        };
+        if mac
+            .path
+            .segments
+            .last()
+            .map_or(true, |segment| segment.ident != "error")
+        {
+            continue;
+        }
+        let error = mac.parse_body::<InlineError>()?;
+        let name = &error.name;
+        *ty = match module {
+            Some(module) => syn::parse_quote!(#module::#name),
+            None => syn::parse_quote!(#name),
+        };
+        errors.push(error);
+    }
+    Ok(errors)
+}
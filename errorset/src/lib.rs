@@ -3,29 +3,87 @@
 
 extern crate proc_macro;
 
-use core::panic;
+mod backtrace;
+mod docs;
+mod generics;
+mod infer;
+mod inline;
+mod paths;
+mod trace;
+mod variant;
 
 use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::quote;
-use std::collections::HashSet;
+use quote::{quote, quote_spanned, ToTokens};
+use std::collections::{HashMap, HashSet};
 use syn::{
+    ext::IdentExt,
     parse::{Parse, ParseStream, Result},
     parse_macro_input,
     punctuated::Punctuated,
+    spanned::Spanned,
     token::PathSep,
-    Ident, ImplItemFn, ItemFn, ItemImpl, PathArguments, PathSegment, ReturnType, Token, Type, TypePath, Visibility,
+    Generics, Ident, ImplItemFn, ItemFn, ItemImpl, ItemMod, ItemType, PathArguments, PathSegment, ReturnType, Token,
+    Type, TypePath, Visibility,
 };
 
+#[derive(Clone)]
 struct ErrorsetArgs {
     visibility: Visibility,
     module: Option<Ident>,
+    derives: Vec<syn::Path>,
+    attrs: Vec<syn::Meta>,
+    name: Option<EnumName>,
+    enum_visibility: Option<Visibility>,
+    use_set: Option<syn::Path>,
+    self_prefix: bool,
+    self_mod: bool,
+    infer: bool,
+    lint: bool,
+    trace: bool,
+    backtrace: bool,
+}
+
+/// Name of the generated enum: either the exact identifier or a template,
+/// where `{}` is replaced with the Pascal-cased function name.
+#[derive(Clone)]
+enum EnumName {
+    Exact(Ident),
+    Template(syn::LitStr),
+}
+
+impl EnumName {
+    fn resolve(&self, fn_name: &Ident) -> Result<Ident> {
+        match self {
+            EnumName::Exact(ident) => Ok(ident.clone()),
+            EnumName::Template(template) => {
+                let name = template
+                    .value()
+                    .replace("{}", &fn_name.to_string().to_case(Case::Pascal));
+                let mut ident = syn::parse_str::<Ident>(&name)
+                    .map_err(|_| syn::Error::new_spanned(template, "Template must produce a valid identifier"))?;
+                ident.set_span(template.span());
+                Ok(ident)
+            }
+        }
+    }
 }
 
 impl Parse for ErrorsetArgs {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut module = None;
+        let mut derives = Vec::new();
+        let mut attrs = Vec::new();
+        let mut name = None;
+        let mut enum_visibility = None;
+        let mut use_set = None;
+        let mut self_prefix = false;
+        let mut self_mod = false;
+        let mut infer = false;
+        let mut lint = false;
+        let mut trace = false;
+        let mut backtrace = false;
 
         // try parse Visibility of module
         let visibility: Visibility = input.parse()?;
@@ -37,7 +95,133 @@ impl Parse for ErrorsetArgs {
             module = Some(mod_name);
         }
 
-        Ok(ErrorsetArgs { visibility, module })
+        // options follow the module definition, separated by commas
+        let mut first = module.is_none() && matches!(visibility, Visibility::Inherited);
+        while !input.is_empty() {
+            if !first {
+                input.parse::<Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+            }
+            first = false;
+
+            let option = input.call(Ident::parse_any)?;
+            match option.to_string().as_str() {
+                // derive(Clone, PartialEq) - additional derives for the generated enum
+                "derive" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    derives.extend(Punctuated::<syn::Path, Token![,]>::parse_terminated(&content)?);
+                }
+                // attr(must_use, doc = "...") - arbitrary attributes for the generated enum
+                "attr" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    attrs.extend(Punctuated::<syn::Meta, Token![,]>::parse_terminated(&content)?);
+                }
+                // name = LoadError or name = "{}Error" - exact name or naming template of the generated enum
+                "name" => {
+                    input.parse::<Token![=]>()?;
+                    name = Some(if input.peek(syn::LitStr) {
+                        EnumName::Template(input.parse()?)
+                    } else {
+                        EnumName::Exact(input.parse()?)
+                    });
+                }
+                // vis = pub(crate) - visibility of the generated enum
+                "vis" => {
+                    input.parse::<Token![=]>()?;
+                    enum_visibility = Some(input.parse()?);
+                }
+                // use = ApiErrors - reuse the set declared with `errorset::set!` instead of generating an enum
+                "use" => {
+                    input.parse::<Token![=]>()?;
+                    use_set = Some(input.parse()?);
+                }
+                // self_prefix - prefix the enum name of the method with the self type, like `FooNewErrors`
+                "self_prefix" => self_prefix = true,
+                // self_mod - place enums of methods into the module named after the self type, like `foo_errors`
+                "self_mod" => self_mod = true,
+                // infer - add error types constructed by the function body to the tuple
+                "infer" => infer = true,
+                // lint - warn about error types of the tuple, which are never produced by the function body
+                "lint" => lint = true,
+                // trace - record the function into the trace of the error, when the error is converted into the set.
+                // The expansion refers to `::smarterr::trace`, so `smarterr` must be the direct dependency under this name
+                "trace" => trace = true,
+                // backtrace - add the `backtrace` method, returning the backtrace of the error held by the variant
+                "backtrace" => backtrace = true,
+                _ => return Err(syn::Error::new_spanned(option, "Unknown errorset option")),
+            }
+        }
+
+        let args = ErrorsetArgs {
+            visibility,
+            module,
+            derives,
+            attrs,
+            name,
+            enum_visibility,
+            use_set,
+            self_prefix,
+            self_mod,
+            infer,
+            lint,
+            trace,
+            backtrace,
+        };
+        if let Some(set) = &args.use_set {
+            if args.has_enum_options() {
+                return Err(syn::Error::new_spanned(
+                    set,
+                    "`use` can not be combined with options of the generated enum",
+                ));
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+impl ErrorsetArgs {
+    fn has_enum_options(&self) -> bool {
+        self.module.is_some()
+            || !self.derives.is_empty()
+            || !self.attrs.is_empty()
+            || self.name.is_some()
+            || self.enum_visibility.is_some()
+            || self.trace
+            || self.backtrace
+    }
+
+    /// Combines options of the impl block, which are defaults, with options of the method, which override them.
+    /// Derives and attributes are accumulated.
+    fn merge(&self, method: ErrorsetArgs) -> ErrorsetArgs {
+        if method.use_set.is_some() || (self.use_set.is_some() && method.has_enum_options()) {
+            return method;
+        }
+
+        let (visibility, module) = if method.module.is_some() {
+            (method.visibility, method.module)
+        } else {
+            (self.visibility.clone(), self.module.clone())
+        };
+        ErrorsetArgs {
+            visibility,
+            module,
+            derives: self.derives.iter().cloned().chain(method.derives).collect(),
+            attrs: self.attrs.iter().cloned().chain(method.attrs).collect(),
+            name: method.name.or_else(|| self.name.clone()),
+            enum_visibility: method.enum_visibility.or_else(|| self.enum_visibility.clone()),
+            use_set: self.use_set.clone(),
+            self_prefix: self.self_prefix || method.self_prefix,
+            self_mod: self.self_mod || method.self_mod,
+            infer: self.infer || method.infer,
+            lint: self.lint || method.lint,
+            trace: self.trace || method.trace,
+            backtrace: self.backtrace || method.backtrace,
+        }
     }
 }
 
@@ -47,37 +231,287 @@ pub fn errorset(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as syn::Item);
 
     match input {
-        syn::Item::Fn(item_fn) => handle_function(&args, item_fn),
+        syn::Item::Fn(item_fn) => emit_output(
+            &args,
+            process_fn(&args, &item_fn, None, &infer::Callees::default(), &[]),
+            item_fn,
+        ),
         syn::Item::Impl(item_impl) => handle_impl_block(&args, item_impl),
-        _ => panic!("errorset can only be applied to functions or impl blocks"),
+        syn::Item::Type(item_type) => emit_output(&args, process_type_alias(&args, &item_type, &[]), item_type),
+        syn::Item::Mod(item_mod) => handle_module(&args, item_mod),
+        other => syn::Error::new_spanned(
+            other,
+            "errorset can only be applied to functions, impl blocks, type aliases or modules",
+        )
+        .to_compile_error()
+        .into(),
+    }
+}
+
+/// Named error set: `pub ApiErrors = (NotFound, Denied, Timeout)`
+struct SetDefinition {
+    attrs: Vec<syn::Attribute>,
+    visibility: Visibility,
+    name: Ident,
+    err_types: Punctuated<Type, Token![,]>,
+}
+
+impl Parse for SetDefinition {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let visibility = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let err_types = match input.parse::<Type>()? {
+            Type::Tuple(tuple) => tuple.elems,
+            Type::Paren(paren) => Punctuated::from_iter([*paren.elem]),
+            other => return Err(syn::Error::new_spanned(other, "Expected a tuple of error types")),
+        };
+        check_error_types(&err_types)?;
+        if !input.is_empty() {
+            input.parse::<Token![;]>()?;
+        }
+
+        Ok(SetDefinition { attrs, visibility, name, err_types })
+    }
+}
+
+/// Defines a named error set, which may be shared by many functions with `#[errorset(use = ...)]`.
+///
+/// ```rust,ignore
+/// errorset::set!(pub ApiErrors = (NotFound, Denied, Timeout));
+///
+/// #[errorset(use = ApiErrors)]
+/// fn get(id: u32) -> Result<Item, (NotFound, Denied, Timeout)> {
+///     todo!()
+/// }
+/// ```
+#[proc_macro]
+pub fn set(input: TokenStream) -> TokenStream {
+    let SetDefinition { attrs, visibility, name, err_types } = parse_macro_input!(input as SetDefinition);
+
+    let attrs = attrs.into_iter().map(|attr| attr.meta).collect::<Vec<_>>();
+    let enum_def = enum_definition(&[], &attrs, &visibility, &name, &Generics::default(), &err_types);
+    let len = enum_variants(&err_types, &Generics::default()).len();
+    quote! {
+        #enum_def
+        impl #name {
+            #[doc(hidden)]
+            pub const __ERRORSET_LEN: usize = #len;
+        }
+    }
+    .into()
+}
+
+/// Block with its own error set: `(InvalidData, OutOfBounds) => { ... } match { ... }`
+struct BlockDefinition {
+    err_types: Punctuated<Type, Token![,]>,
+    body: syn::Block,
+    arms: Option<Vec<syn::Arm>>,
+}
+
+impl Parse for BlockDefinition {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let err_types = match input.parse::<Type>()? {
+            Type::Tuple(tuple) => tuple.elems,
+            Type::Paren(paren) => Punctuated::from_iter([*paren.elem]),
+            other => return Err(syn::Error::new_spanned(other, "Expected a tuple of error types")),
+        };
+        input.parse::<Token![=>]>()?;
+        let body = input.parse()?;
+        let arms = if input.parse::<Option<Token![match]>>()?.is_some() {
+            let content;
+            syn::braced!(content in input);
+            let mut arms = Vec::new();
+            while !content.is_empty() {
+                arms.push(content.parse()?);
+            }
+            Some(arms)
+        } else {
+            None
+        };
+
+        Ok(BlockDefinition { err_types, body, arms })
+    }
+}
+
+/// Evaluates the block with its own error set, which is the anonymous enum, like the one generated for functions.
+/// The result may be matched immediately, naming variants of the enum by the error types.
+///
+/// ```rust,ignore
+/// let value = errorset::block!((InvalidData, OutOfBounds) => {
+///     let value = parse(data)?;
+///     check(value)?;
+///     Ok(value)
+/// } match {
+///     Ok(value) => value,
+///     Err(InvalidData(_)) => 0,
+///     Err(OutOfBounds(e)) => e.bound,
+/// });
+/// ```
+#[proc_macro]
+pub fn block(input: TokenStream) -> TokenStream {
+    let BlockDefinition { mut err_types, body, arms } = parse_macro_input!(input as BlockDefinition);
+    let inline_errors = match inline::extract(&mut err_types, None).and_then(|errors| {
+        check_error_types(&err_types)?;
+        Ok(errors)
+    }) {
+        Ok(inline_errors) => inline_errors,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let name = Ident::new("BlockErrors", Span::call_site());
+    let vis = Visibility::Inherited;
+    let enum_def = enum_definition(&[], &[], &vis, &name, &Generics::default(), &err_types);
+    let structs = inline_errors.iter().map(|error| error.definition(&vis));
+    let result = quote! {
+        (|| -> ::core::result::Result<_, #name> #body)()
+    };
+    let result = match arms {
+        Some(mut arms) => {
+            let variants = enum_variants(&err_types, &Generics::default())
+                .into_iter()
+                .map(|(variant, _)| variant)
+                .collect::<Vec<_>>();
+            for arm in &mut arms {
+                paths::qualify_variants(&mut arm.pat, &name, &variants);
+            }
+            quote! {
+                match #result {
+                    #(#arms)*
+                }
+            }
+        }
+        None => result,
+    };
+    quote! {
+        {
+            #enum_def
+            #(#structs)*
+            #result
+        }
+    }
+    .into()
+}
+
+/// Returns the binding of the field resolved at the span of the message, like `__message_binding!(source, "{source}")`,
+/// so the message of `smarterr::error!` may refer to the source and the fields of the markers. Bindings created by
+/// `macro_rules` are hygienic and are not visible to the message otherwise.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __message_binding(input: TokenStream) -> TokenStream {
+    let parser = |input: ParseStream| {
+        let field = input.parse::<Ident>()?;
+        input.parse::<Token![,]>()?;
+        let message = input.parse::<syn::LitStr>()?;
+        Ok(Ident::new(&field.to_string(), message.span()))
+    };
+    match syn::parse::Parser::parse(parser, input) {
+        Ok(binding) => binding.into_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
     }
 }
 
 struct Output {
     enum_def: proc_macro2::TokenStream,
-    fn_def: proc_macro2::TokenStream,
+    item_def: proc_macro2::TokenStream,
 }
 
-fn process_fn(args: &ErrorsetArgs, item_fn: &ItemFn) -> Result<Option<Output>> {
-    // Extract the function name and convert it to camel-case for the enum name
-    let fn_name = &item_fn.sig.ident;
-    let enum_name = Ident::new(
-        &format!("{}Errors", fn_name.to_string().to_case(Case::Pascal)),
-        Span::call_site(),
-    );
+/// Impl block the processed method belongs to
+struct ImplContext {
+    self_ty: Type,
+    generics: Generics,
+}
 
-    // Extract the return type from the function signature
-    let output_type = match &item_fn.sig.output {
-        ReturnType::Type(_, ty) => ty,
-        _ => {
-            return Err(syn::Error::new_spanned(
-                &item_fn.sig.output,
-                "Function must have a valid return type",
-            ))
+impl ImplContext {
+    fn new(item_impl: &ItemImpl) -> Self {
+        ImplContext {
+            self_ty: (*item_impl.self_ty).clone(),
+            generics: item_impl.generics.clone(),
+        }
+    }
+
+    /// Returns the name of the self type without generic arguments
+    fn self_ident(&self) -> Result<&Ident> {
+        match &self.self_ty {
+            Type::Path(TypePath { path, .. }) => Ok(&path.segments.last().unwrap().ident),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "Expected the self type to be a type path",
+            )),
         }
+    }
+
+    /// Returns the module named after the self type: `foo_errors` for `Foo` and `errors` module
+    fn self_module(&self, module: Option<&Ident>) -> Result<Ident> {
+        let self_ident = self.self_ident()?;
+        let name = match module {
+            Some(module) => format!("{}_{}", self_ident.to_string().to_case(Case::Snake), module),
+            None => self_ident.to_string().to_case(Case::Snake),
+        };
+        Ok(Ident::new(&name, module.map_or_else(|| self_ident.span(), Ident::span)))
+    }
+}
+
+/// Returns the name of the enum generated for the item (function or type alias)
+fn enum_name(args: &ErrorsetArgs, item_name: &Ident, impl_ctx: Option<&ImplContext>) -> Result<Ident> {
+    // the exact name is never prefixed with the self type
+    if let Some(EnumName::Exact(name)) = &args.name {
+        return Ok(name.clone());
+    }
+
+    let item_name = match impl_ctx {
+        Some(ctx) if args.self_prefix => Ident::new(
+            &format!("{}{}", ctx.self_ident()?, item_name.to_string().to_case(Case::Pascal)),
+            item_name.span(),
+        ),
+        _ => item_name.clone(),
     };
+    match &args.name {
+        Some(name) => name.resolve(&item_name),
+        // Convert the item name to camel-case for the enum name
+        None => Ok(Ident::new(
+            &format!("{}Errors", item_name.to_string().to_case(Case::Pascal)),
+            Span::call_site(),
+        )),
+    }
+}
+
+/// Returns the path of the error type, which replaces the error tuple
+fn error_type_path(args: &ErrorsetArgs, enum_name: &Ident, generics: &Generics) -> proc_macro2::TokenStream {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    if let Some(set) = &args.use_set {
+        quote! { #set }
+    } else if let Some(module) = &args.module {
+        quote! { #module::#enum_name #ty_generics }
+    } else {
+        quote! { #enum_name #ty_generics }
+    }
+}
+
+/// Type like `Result<T, (Error1, Error2)>`, split into parts
+struct ErrorTuple {
+    path: syn::Path,
+    ok_type: syn::GenericArgument,
+    err_types: Punctuated<Type, Token![,]>,
+    /// The tuple is replaced with `_`, so error types are inferred from the function body
+    infer: bool,
+}
+
+impl ErrorTuple {
+    /// Returns the new type like `Result<T, #error_type>`
+    fn with_error(&self, error_type: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let ErrorTuple { path, ok_type, .. } = self;
+        quote! {
+            #path<#ok_type, #error_type>
+        }
+    }
+}
 
-    let (new_return_type, err_types) = if let Type::Path(TypePath { path, .. }) = &**output_type {
+/// Splits the type like `Result<T, (Error1, Error2)>` into the type path, the first generic argument and the error tuple.
+/// Returns `None` if the second generic argument is not a tuple.
+fn split_result_type(ty: &Type) -> Result<Option<ErrorTuple>> {
+    if let Type::Path(TypePath { path, .. }) = ty {
         if let Some(last_segment) = path.segments.last() {
             if let PathArguments::AngleBracketed(ref params) = last_segment.arguments {
                 if params.args.len() != 2 {
@@ -87,36 +521,9 @@ fn process_fn(args: &ErrorsetArgs, item_fn: &ItemFn) -> Result<Option<Output>> {
                     ));
                 }
 
-                match params.args.iter().nth(1).unwrap() {
-                    syn::GenericArgument::Type(Type::Tuple(tuple)) => {
-                        let mut punctuated = Punctuated::<PathSegment, PathSep>::new();
-                        for seg in path.segments.iter() {
-                            punctuated.push_value(seg.ident.clone().into());
-                            // Add separator if there are more segments
-                            if punctuated.len() < path.segments.len() {
-                                punctuated.push_punct(PathSep::default());
-                            }
-                        }
-                        let new_path = syn::Path {
-                            leading_colon: path.leading_colon.clone(),
-                            segments: punctuated,
-                        };
-
-                        // Create new return type with the same name and the first generic parameter
-                        // The second parameter is the enum with error types
-                        let first_generic_arg = params.args.iter().next().unwrap();
-                        let new_return_type = if let Some(module) = &args.module {
-                            quote! {
-                                #new_path<#first_generic_arg, #module::#enum_name>
-                            }
-                        } else {
-                            quote! {
-                                #new_path<#first_generic_arg, #enum_name>
-                            }
-                        };
-                        let err_types = tuple.elems.clone();
-                        (new_return_type, err_types)
-                    }
+                let (err_types, infer) = match params.args.iter().nth(1).unwrap() {
+                    syn::GenericArgument::Type(Type::Tuple(tuple)) => (tuple.elems.clone(), false),
+                    syn::GenericArgument::Type(Type::Infer(_)) => (Punctuated::new(), true),
                     syn::GenericArgument::Type(Type::Paren(_)) | syn::GenericArgument::Type(Type::Path(_)) => {
                         // If the second argument is defined as `(Error1)`, it does not determined as a tuple, just leave it as is
                         // The same if the second argument is a regular type
@@ -126,65 +533,275 @@ fn process_fn(args: &ErrorsetArgs, item_fn: &ItemFn) -> Result<Option<Output>> {
                         return Err(syn::Error::new_spanned(
                             other,
                             "Expected the second generic argument to be a tuple",
-                        ));
+                        ))
+                    }
+                };
+                let mut punctuated = Punctuated::<PathSegment, PathSep>::new();
+                for seg in path.segments.iter() {
+                    punctuated.push_value(seg.ident.clone().into());
+                    // Add separator if there are more segments
+                    if punctuated.len() < path.segments.len() {
+                        punctuated.push_punct(PathSep::default());
                     }
                 }
+                let new_path = syn::Path {
+                    leading_colon: path.leading_colon,
+                    segments: punctuated,
+                };
+
+                // New type keeps the same name and the first generic parameter
+                // The second parameter is replaced with the enum with error types
+                Ok(Some(ErrorTuple {
+                    path: new_path,
+                    ok_type: params.args.iter().next().unwrap().clone(),
+                    err_types,
+                    infer,
+                }))
             } else {
-                return Err(syn::Error::new_spanned(
+                Err(syn::Error::new_spanned(
                     last_segment,
                     "Expected angle-bracketed generic arguments",
-                ));
+                ))
             }
         } else {
-            return Err(syn::Error::new_spanned(
+            Err(syn::Error::new_spanned(
                 path,
                 "Expected a valid type path for the generic type",
-            ));
+            ))
         }
     } else {
-        return Err(syn::Error::new_spanned(
-            output_type,
-            "Function must return a generic type with 2 parameters",
-        ));
-    };
+        Err(syn::Error::new_spanned(ty, "Expected a generic type with 2 parameters"))
+    }
+}
 
-    // Generate enum variants for each error type
-    let mut seen = HashSet::new();
-    let enum_variants = err_types
-        .iter()
-        .filter(|ty| match ty {
-            Type::Path(TypePath { path, .. }) => seen.insert(path.segments.last().unwrap().ident.to_string()),
-            _ => true,
-        })
-        .map(|ty| {
-            let ty_name = match ty {
-                Type::Path(TypePath { path, .. }) => path.segments.last().unwrap().ident.clone(),
-                _ => return quote! {}, // skip invalid
-            };
-            quote! {
-                #[error(transparent)]
-                #ty_name(#[from] #ty),
+/// Item the enum is generated for: the function or the type alias
+struct SetItem<'a> {
+    vis: &'a Visibility,
+    /// Doc comment of the enum, linking back to the item
+    doc: syn::Meta,
+    /// Name of the function recorded into the trace, like `parse` or `Parser::parse` for methods
+    function: Option<String>,
+    /// Names of the enums generated for other items into the same module
+    siblings: &'a [Ident],
+}
+
+/// Generates the enum for the error tuple or, if the set is reused, verifies the tuple matches it
+fn generate_enum(
+    args: &ErrorsetArgs,
+    item: SetItem,
+    enum_name: &Ident,
+    generics: &Generics,
+    err_types: &Punctuated<Type, Token![,]>,
+    inline_errors: &[inline::InlineError],
+) -> proc_macro2::TokenStream {
+    if let Some(set) = &args.use_set {
+        // the set is defined elsewhere, just verify the tuple matches it
+        set_assertions(set, err_types)
+    } else {
+        let enum_vis = if let Some(vis) = &args.enum_visibility {
+            vis.clone()
+        } else if args.module.is_some() {
+            // use pub visibility for the enum if it's inside a module
+            syn::Visibility::Public(Default::default())
+        } else {
+            item.vis.clone()
+        };
+        let attrs = [item.doc]
+            .into_iter()
+            .chain(args.attrs.iter().cloned())
+            .collect::<Vec<_>>();
+        let mut err_types = err_types.clone();
+        let mut generics = generics.clone();
+        let kind_name = trace::kind_name(enum_name);
+        if args.module.is_some() {
+            // the enum is placed into the module, so relative paths must start from its parent
+            let shadowed = [enum_name, &kind_name]
+                .into_iter()
+                .chain(item.siblings)
+                .collect::<Vec<_>>();
+            paths::relocate(&mut err_types, &mut generics, &shadowed);
+        }
+        let enum_def = match &item.function {
+            Some(function) if args.trace => {
+                let kind_doc = format!("Kinds of errors of [`{enum_name}`].");
+                let kind_def = enum_definition(
+                    &args.derives,
+                    &[syn::parse_quote!(doc = #kind_doc)],
+                    &enum_vis,
+                    &kind_name,
+                    &generics,
+                    &err_types,
+                );
+                let traced_def = trace::traced_definition(
+                    &args.derives,
+                    &attrs,
+                    &enum_vis,
+                    enum_name,
+                    &generics,
+                    &err_types,
+                    function,
+                );
+                let backtrace = args.backtrace.then(|| {
+                    let variants = enum_variants(&err_types, &generics);
+                    let kind_backtrace = backtrace::enum_backtrace(&kind_name, &generics, &variants);
+                    let traced_backtrace = backtrace::traced_backtrace(enum_name, &generics);
+                    quote! {
+                        #kind_backtrace
+                        #traced_backtrace
+                    }
+                });
+                quote! {
+                    #kind_def
+                    #traced_def
+                    #backtrace
+                }
             }
-        });
+            _ => {
+                let enum_def = enum_definition(&args.derives, &attrs, &enum_vis, enum_name, &generics, &err_types);
+                let backtrace = args
+                    .backtrace
+                    .then(|| backtrace::enum_backtrace(enum_name, &generics, &enum_variants(&err_types, &generics)));
+                quote! {
+                    #enum_def
+                    #backtrace
+                }
+            }
+        };
+        let structs = inline_errors.iter().map(|error| error.definition(&enum_vis));
+        quote! {
+            #enum_def
+            #(#structs)*
+        }
+    }
+}
 
-    // Generate the enum definition
-    let enum_vis = if args.module.is_some() {
-        // use pub visibility for the enum if it's inside a module
-        syn::Visibility::Public(Default::default())
+/// Replaces inline error definitions of the tuple with paths to the structs generated along with the enum
+fn inline_errors(args: &ErrorsetArgs, err_types: &mut Punctuated<Type, Token![,]>) -> Result<Vec<inline::InlineError>> {
+    let inline_errors = inline::extract(&mut *err_types, args.module.as_ref())?;
+    check_error_types(err_types)?;
+    match inline_errors.first() {
+        Some(error) if args.use_set.is_some() => Err(syn::Error::new_spanned(
+            &error.name,
+            "Inline error definitions can not be used with the named set",
+        )),
+        _ => Ok(inline_errors),
+    }
+}
+
+/// Returns the intra-doc link to the item from the generated enum
+fn item_link(args: &ErrorsetArgs, item_name: &Ident, impl_ctx: Option<&ImplContext>) -> String {
+    let path = match impl_ctx.and_then(|ctx| ctx.self_ident().ok()) {
+        Some(self_ident) => format!("{self_ident}::{item_name}"),
+        None => item_name.to_string(),
+    };
+    if args.module.is_some() {
+        format!("super::{path}")
+    } else {
+        path
+    }
+}
+
+/// Returns generics of the enum, which are used by the error types. The named set is never generic.
+fn error_generics(
+    args: &ErrorsetArgs,
+    err_types: &Punctuated<Type, Token![,]>,
+    item_generics: &Generics,
+    impl_ctx: Option<&ImplContext>,
+) -> Result<Generics> {
+    if args.use_set.is_some() {
+        Ok(Generics::default())
     } else {
-        item_fn.vis.clone()
+        generics::enum_generics(
+            &err_types.iter().collect::<Vec<_>>(),
+            item_generics,
+            impl_ctx.map(|ctx| &ctx.generics),
+        )
+    }
+}
+
+fn process_fn(
+    args: &ErrorsetArgs,
+    item_fn: &ItemFn,
+    impl_ctx: Option<&ImplContext>,
+    callees: &infer::Callees,
+    siblings: &[Ident],
+) -> Result<Option<Output>> {
+    let enum_name = enum_name(args, &item_fn.sig.ident, impl_ctx)?;
+
+    // Extract the return type from the function signature
+    let output_type = match &item_fn.sig.output {
+        ReturnType::Type(_, ty) => ty,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item_fn.sig.output,
+                "Function must have a valid return type",
+            ))
+        }
     };
-    let enum_def = quote! {
-        #[derive(::thiserror::Error, Debug)]
-        #enum_vis enum #enum_name {
-            #(#enum_variants)*
+
+    let Some(mut error_tuple) = split_result_type(output_type)? else {
+        return Ok(None);
+    };
+    if error_tuple.infer || args.infer {
+        // the enum of the function itself can not be its variant
+        let own_type = error_type_path(args, &enum_name, &Generics::default()).to_string();
+        for ty in infer::infer_errors(&item_fn.block, callees)? {
+            if ty.to_token_stream().to_string() != own_type {
+                error_tuple.err_types.push(ty);
+            }
+        }
+        if error_tuple.err_types.is_empty() {
+            return Err(syn::Error::new_spanned(
+                output_type,
+                "No error types are found in the function body",
+            ));
         }
+    }
+    paths::resolve_self(&mut error_tuple.err_types, impl_ctx.map(|ctx| &ctx.self_ty))?;
+    let inline_errors = inline_errors(args, &mut error_tuple.err_types)?;
+    let generics = error_generics(args, &error_tuple.err_types, &item_fn.sig.generics, impl_ctx)?;
+    let new_return_type = error_tuple.with_error(&error_type_path(args, &enum_name, &generics));
+
+    let fn_name = &item_fn.sig.ident;
+    let function = match impl_ctx.and_then(|ctx| ctx.self_ident().ok()) {
+        Some(self_ident) => format!("{self_ident}::{fn_name}"),
+        None => fn_name.to_string(),
+    };
+    let enum_doc = docs::enum_doc(
+        "Errors returned by",
+        &fn_name.to_string(),
+        &item_link(args, fn_name, impl_ctx),
+    );
+    let item = SetItem {
+        vis: &item_fn.vis,
+        doc: enum_doc,
+        function: Some(function),
+        siblings,
     };
+    let enum_def = generate_enum(
+        args,
+        item,
+        &enum_name,
+        &generics,
+        &error_tuple.err_types,
+        &inline_errors,
+    );
 
+    // Append the `# Errors` section to the documentation of the function
+    let variants = enum_variants(&error_tuple.err_types, &generics);
+    let fn_attrs = item_fn
+        .attrs
+        .iter()
+        .cloned()
+        .chain(docs::errors_section(&variants, &generics, &inline_errors))
+        .collect::<Vec<_>>();
     let fn_sig = &item_fn.sig;
-    let fn_attrs = &item_fn.attrs;
     let fn_vis = &item_fn.vis;
-    let fn_body = &item_fn.block;
+    let fn_body = if args.lint {
+        lint_unused_errors(fn_name, &item_fn.block, &variants, callees)
+    } else {
+        (*item_fn.block).clone()
+    };
 
     let mut new_sig = fn_sig.clone();
     new_sig.output = syn::parse2(quote! { -> #new_return_type }).unwrap();
@@ -196,109 +813,670 @@ fn process_fn(args: &ErrorsetArgs, item_fn: &ItemFn) -> Result<Option<Output>> {
         #fn_body
     };
 
-    Ok(Some(Output { enum_def, fn_def: new_fn }))
+    Ok(Some(Output { enum_def, item_def: new_fn }))
 }
 
-fn handle_function(args: &ErrorsetArgs, item_fn: ItemFn) -> TokenStream {
-    match process_fn(args, &item_fn) {
-        Ok(Some(Output { enum_def, fn_def })) => {
-            if let Some(module) = &args.module {
-                let vis = &args.visibility;
-                quote! {
-                    #vis mod #module {
-                        use super::*;
-                        #enum_def
+/// Inserts a deprecated constant into the body for each error type, which the body never produces, so the compiler
+/// warns about it at the type of the tuple. The warning is silenced with `#[allow(deprecated)]`.
+fn lint_unused_errors(
+    fn_name: &Ident,
+    block: &syn::Block,
+    variants: &[(Ident, &Type)],
+    callees: &infer::Callees,
+) -> syn::Block {
+    let mut block = block.clone();
+    let markers = infer::unused_variants(&block, callees, variants)
+        .into_iter()
+        .map(|(name, ty)| -> syn::Stmt {
+            let note =
+                format!("`{name}` is declared in the error tuple of `{fn_name}`, but is never produced by its body");
+            let marker = Ident::new(&format!("__ERRORSET_UNUSED_{name}"), ty.span());
+            syn::parse2(quote_spanned! { ty.span() =>
+                #[allow(non_upper_case_globals, dead_code)]
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    const #marker: () = ();
+                    #marker
+                };
+            })
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    block.stmts.splice(0..0, markers);
+    block
+}
+
+fn process_type_alias(args: &ErrorsetArgs, item_type: &ItemType, siblings: &[Ident]) -> Result<Option<Output>> {
+    let enum_name = enum_name(args, &item_type.ident, None)?;
+    let Some(mut error_tuple) = split_result_type(&item_type.ty)? else {
+        return Ok(None);
+    };
+    if error_tuple.infer {
+        return Err(syn::Error::new_spanned(
+            &item_type.ty,
+            "Error types of the type alias can not be inferred",
+        ));
+    }
+    if args.trace {
+        return Err(syn::Error::new_spanned(
+            &item_type.ident,
+            "Errors of the type alias can not be traced, since it's not a function",
+        ));
+    }
+    paths::resolve_self(&mut error_tuple.err_types, None)?;
+    let inline_errors = inline_errors(args, &mut error_tuple.err_types)?;
+    let generics = error_generics(args, &error_tuple.err_types, &item_type.generics, None)?;
+    let new_type = error_tuple.with_error(&error_type_path(args, &enum_name, &generics));
+
+    let alias_name = &item_type.ident;
+    let enum_doc = docs::enum_doc(
+        "Errors of the type alias",
+        &alias_name.to_string(),
+        &item_link(args, alias_name, None),
+    );
+    let item = SetItem {
+        vis: &item_type.vis,
+        doc: enum_doc,
+        function: None,
+        siblings,
+    };
+    let enum_def = generate_enum(
+        args,
+        item,
+        &enum_name,
+        &generics,
+        &error_tuple.err_types,
+        &inline_errors,
+    );
+
+    let variants = enum_variants(&error_tuple.err_types, &generics);
+    let errors_section = docs::errors_section(&variants, &generics, &inline_errors);
+    let ItemType { attrs, vis, ident, generics, .. } = item_type;
+    let attrs = attrs.iter().chain(&errors_section);
+    let where_clause = &generics.where_clause;
+    let item_def = quote! {
+        #(#attrs)*
+        #vis type #ident #generics #where_clause = #new_type;
+    };
+
+    Ok(Some(Output { enum_def, item_def }))
+}
+
+/// Returns the path of the error type, looking through references, like `io::Error` for `&'a io::Error`
+fn error_path(ty: &Type) -> Option<&syn::Path> {
+    match ty {
+        Type::Path(TypePath { path, .. }) => Some(path),
+        Type::Reference(reference) => error_path(&reference.elem),
+        Type::Paren(paren) => error_path(&paren.elem),
+        Type::Group(group) => error_path(&group.elem),
+        _ => None,
+    }
+}
+
+/// Returns an error for each error type, which is neither a type path nor a reference to it
+fn check_error_types(err_types: &Punctuated<Type, Token![,]>) -> Result<()> {
+    err_types
+        .iter()
+        .filter(|ty| error_path(ty).is_none())
+        .map(|ty| {
+            syn::Error::new_spanned(
+                ty,
+                "Error types must be type paths or references to them, like `&'a Error1`",
+            )
+        })
+        .reduce(|mut errors, error| {
+            errors.combine(error);
+            errors
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Returns variants of the enum, which are names and error types of the tuple, skipping the duplicates.
+/// Types depending on generic parameters, like `S::Error`, are named after the whole path, like `SError`.
+/// References are named after the referenced type, so `&'a Error1` is `Error1`.
+fn enum_variants<'a>(err_types: &'a Punctuated<Type, Token![,]>, generics: &Generics) -> Vec<(Ident, &'a Type)> {
+    let mut seen = HashSet::new();
+    err_types
+        .iter()
+        .filter_map(|ty| {
+            // other types are reported by `check_error_types`
+            let path = error_path(ty)?;
+            if generics::depends_on_params(ty, generics) {
+                let name = path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .collect::<String>();
+                Some((Ident::new(&name.to_case(Case::Pascal), path.span()), ty))
+            } else {
+                Some((path.segments.last().unwrap().ident.clone(), ty))
+            }
+        })
+        .filter(|(name, _)| seen.insert(name.to_string()))
+        .collect()
+}
+
+fn enum_definition(
+    derives: &[syn::Path],
+    attrs: &[syn::Meta],
+    enum_vis: &Visibility,
+    enum_name: &Ident,
+    generics: &Generics,
+    err_types: &Punctuated<Type, Token![,]>,
+) -> proc_macro2::TokenStream {
+    let err_types = enum_variants(err_types, generics);
+
+    // Generate enum variants for each error type
+    let transparent = cfg!(feature = "thiserror").then(|| quote! { #[error(transparent)] });
+    let enum_variants = err_types.iter().map(|(ty_name, ty)| {
+        quote! {
+            #transparent
+            #ty_name(#ty),
+        }
+    });
+
+    // `From` is implemented manually, since `#[from]` does not support borrowed error types.
+    // It can not be implemented for types depending on generic parameters, since they may overlap with other types
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let from_impls = err_types
+        .iter()
+        .filter(|(_, ty)| !generics::depends_on_params(ty, generics))
+        .map(|(ty_name, ty)| {
+            quote! {
+                impl #impl_generics ::core::convert::From<#ty> for #enum_name #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        Self::#ty_name(source)
                     }
-                    #fn_def
                 }
-            } else {
-                quote! {
-                    #enum_def
-                    #fn_def
+            }
+        });
+
+    // Debug is implemented manually, so generic enums require only the error types to be Debug
+    let debug_arms = err_types.iter().map(|(ty_name, _)| {
+        quote! {
+            Self::#ty_name(ref inner) => f.debug_tuple(stringify!(#ty_name)).field(inner).finish(),
+        }
+    });
+    let debug_generics = bounded_generics(generics, &err_types, quote!(::core::fmt::Debug));
+    let (_, _, debug_where_clause) = debug_generics.split_for_impl();
+
+    let (derive, error_impls) = if cfg!(feature = "thiserror") {
+        (quote! { #[derive(::thiserror::Error #(, #derives)*)] }, quote! {})
+    } else {
+        let derive = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+        (derive.unwrap_or_default(), error_impls(enum_name, generics, &err_types))
+    };
+    let as_dyn_error = variant::as_dyn_error();
+    let variant = variant::enum_variant(enum_name, generics, &err_types);
+    quote! {
+        #derive
+        #(#[#attrs])*
+        #enum_vis enum #enum_name #generics #where_clause {
+            #(#enum_variants)*
+        }
+
+        // the traits reaching the held error are shared by the implementations of the enum only
+        const _: () = {
+            #as_dyn_error
+
+            #error_impls
+
+            #variant
+        };
+
+        impl #impl_generics ::core::fmt::Debug for #enum_name #ty_generics #debug_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match *self {
+                    #(#debug_arms)*
                 }
             }
         }
-        Ok(None) => quote! { #item_fn },
-        Err(e) => e.to_compile_error(),
+
+        #(#from_impls)*
     }
-    .into()
 }
 
-fn handle_impl_block(args: &ErrorsetArgs, item_impl: ItemImpl) -> TokenStream {
-    let mut new_items = Vec::new();
-    let mut new_enums = Vec::new();
+/// Implements `Display` and `Error` of the enum, delegating both to the variant, like `#[error(transparent)]` does.
+/// Generic enums require only the error types to implement the traits. The source is reached with `AsDynError`,
+/// which is defined along with the implementations.
+fn error_impls(enum_name: &Ident, generics: &Generics, err_types: &[(Ident, &Type)]) -> proc_macro2::TokenStream {
+    let display_arms = err_types.iter().map(|(ty_name, _)| {
+        quote! {
+            Self::#ty_name(ref inner) => ::core::fmt::Display::fmt(inner, f),
+        }
+    });
+    let source_arms = err_types.iter().map(|(ty_name, _)| {
+        quote! {
+            Self::#ty_name(ref inner) => ::core::error::Error::source(inner.__as_dyn_error()),
+        }
+    });
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let display_generics = bounded_generics(generics, err_types, quote!(::core::fmt::Display));
+    let error_generics = bounded_generics(generics, err_types, quote!(::core::error::Error));
+    let (_, _, display_where_clause) = display_generics.split_for_impl();
+    let (_, _, error_where_clause) = error_generics.split_for_impl();
 
-    for item in item_impl.items {
-        if let syn::ImplItem::Fn(method) = &item {
-            let mut new_attrs = Vec::new();
-            let mut marked = false;
-
-            for attr in &method.attrs {
-                if attr.path().is_ident("errorset") {
-                    if attr.meta.require_path_only().is_err() {
-                        return syn::Error::new_spanned(
-                            attr,
-                            "errorset attribute must not have arguments inside impl blocks",
-                        )
-                        .to_compile_error()
-                        .into();
-                    }
-                    marked = true;
-                } else {
-                    new_attrs.push(attr.clone());
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #enum_name #ty_generics #display_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match *self {
+                    #(#display_arms)*
                 }
             }
+        }
+
+        impl #impl_generics ::core::error::Error for #enum_name #ty_generics #error_where_clause {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                match *self {
+                    #(#source_arms)*
+                }
+            }
+        }
+    }
+}
+
+/// Returns generics of the enum, where error types mentioning its generic parameters are bounded by the trait
+fn bounded_generics(generics: &Generics, err_types: &[(Ident, &Type)], bound: proc_macro2::TokenStream) -> Generics {
+    let mut bounded = generics.clone();
+    let dependent = err_types
+        .iter()
+        .filter(|(_, ty)| generics::mentions_params(ty, generics))
+        .collect::<Vec<_>>();
+    if !dependent.is_empty() {
+        let where_clause = bounded.make_where_clause();
+        for (_, ty) in dependent {
+            where_clause.predicates.push(syn::parse_quote!(#ty: #bound));
+        }
+    }
+    bounded
+}
 
-            if !marked {
-                new_items.push(item);
-                continue;
+/// Verifies at compile time that the error tuple is exactly the set declared with `errorset::set!`
+fn set_assertions(set: &syn::Path, err_types: &Punctuated<Type, Token![,]>) -> proc_macro2::TokenStream {
+    let err_types = enum_variants(err_types, &Generics::default());
+    let len = err_types.len();
+    let from_assertions = err_types.iter().map(|(_, ty)| {
+        quote_spanned! { ty.span() =>
+            let _: fn() = assert_from::<#ty>;
+        }
+    });
+    let message = format!("the error tuple does not match the set `{}`", quote!(#set));
+    quote! {
+        const _: () = {
+            fn assert_from<E>()
+            where
+                #set: ::core::convert::From<E>,
+            {
             }
+            #(#from_assertions)*
+            ::core::assert!(<#set>::__ERRORSET_LEN == #len, #message);
+        };
+    }
+}
+
+/// Enum generated for the item along with the module it should be placed in
+struct GeneratedEnum {
+    module: Option<(Visibility, Ident)>,
+    enum_def: proc_macro2::TokenStream,
+}
+
+impl GeneratedEnum {
+    fn new(args: &ErrorsetArgs, enum_def: proc_macro2::TokenStream) -> Self {
+        let module = args.module.clone().map(|module| (args.visibility.clone(), module));
+        GeneratedEnum { module, enum_def }
+    }
+}
+
+/// Names of the enums generated into each module, which shadow the names of its parent module
+#[derive(Clone, Default)]
+struct ModuleEnums(HashMap<String, Vec<Ident>>);
+
+impl ModuleEnums {
+    /// Adds the enum generated for the item along with its kind, if the enum is placed into the module
+    fn add(&mut self, args: &ErrorsetArgs, item_name: &Ident, impl_ctx: Option<&ImplContext>) {
+        let (Some(module), None) = (&args.module, &args.use_set) else {
+            return;
+        };
+        // invalid names are reported when the item is processed
+        if let Ok(enum_name) = enum_name(args, item_name, impl_ctx) {
+            let names = self.0.entry(module.to_string()).or_default();
+            names.push(trace::kind_name(&enum_name));
+            names.push(enum_name);
+        }
+    }
+
+    /// Returns the enums placed into the module of the item
+    fn get(&self, args: &ErrorsetArgs) -> &[Ident] {
+        args.module
+            .as_ref()
+            .and_then(|module| self.0.get(&module.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Emits generated enums, grouping them by the module they should be placed in
+fn place_enums(enums: Vec<GeneratedEnum>) -> proc_macro2::TokenStream {
+    let mut outside = Vec::new();
+    let mut modules: Vec<(Visibility, Ident, Vec<proc_macro2::TokenStream>)> = Vec::new();
+    for GeneratedEnum { module, enum_def } in enums {
+        match module {
+            Some((vis, module)) => match modules.iter_mut().find(|(_, name, _)| *name == module) {
+                Some((_, _, defs)) => defs.push(enum_def),
+                None => modules.push((vis, module, vec![enum_def])),
+            },
+            None => outside.push(enum_def),
+        }
+    }
 
-            let item_fn = ItemFn {
-                attrs: new_attrs,
-                vis: method.vis.clone(),
-                sig: method.sig.clone(),
-                block: Box::new(method.block.clone()),
-            };
-
-            match process_fn(args, &item_fn) {
-                Ok(Some(Output { enum_def, fn_def })) => {
-                    let impl_item = syn::parse2::<ImplItemFn>(fn_def).expect("Invalid method reparse");
-                    new_items.push(impl_item.into());
-                    new_enums.push(enum_def);
+    let modules = modules.into_iter().map(|(vis, module, defs)| {
+        quote! {
+            #vis mod #module {
+                use super::*;
+                #(#defs)*
+            }
+        }
+    });
+    quote! {
+        #(#modules)*
+        #(#outside)*
+    }
+}
+
+/// Emits the processed item, placing the generated enum into the module if it was defined
+fn emit_output(args: &ErrorsetArgs, output: Result<Option<Output>>, item: impl ToTokens) -> TokenStream {
+    match output {
+        Ok(Some(Output { enum_def, item_def })) => {
+            let enums = place_enums(vec![GeneratedEnum::new(args, enum_def)]);
+            quote! {
+                #enums
+                #item_def
+            }
+        }
+        Ok(None) => quote! { #item },
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
+
+/// Returns true if the attributes contain `#[errorset]` attribute
+fn has_errorset_attr(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("errorset"))
+}
+
+/// Returns true if the type uses the `Result<T, (Error1, Error2)>` or `Result<T, _>` form
+fn has_error_tuple(ty: &Type) -> bool {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return false;
+    };
+    let Some(PathArguments::AngleBracketed(params)) = path.segments.last().map(|segment| &segment.arguments) else {
+        return false;
+    };
+    params.args.len() == 2
+        && match params.args.iter().nth(1) {
+            Some(syn::GenericArgument::Type(Type::Tuple(tuple))) => !tuple.elems.is_empty(),
+            Some(syn::GenericArgument::Type(Type::Infer(_))) => true,
+            _ => false,
+        }
+}
+
+fn returns_error_tuple(sig: &syn::Signature) -> bool {
+    matches!(&sig.output, ReturnType::Type(_, ty) if has_error_tuple(ty))
+}
+
+/// Returns the error type of the function, which is known before the function is processed: the enum generated
+/// for the explicit tuple. Generic functions are skipped, since generic arguments of their enums are unknown.
+fn callee_error_type(args: &ErrorsetArgs, sig: &syn::Signature, impl_ctx: Option<&ImplContext>) -> Option<Type> {
+    if !sig.generics.params.is_empty() || impl_ctx.is_some_and(|ctx| !ctx.generics.params.is_empty()) {
+        return None;
+    }
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return None;
+    };
+    match split_result_type(ty) {
+        Ok(Some(error_tuple)) if !error_tuple.infer => {
+            let enum_name = enum_name(args, &sig.ident, impl_ctx).ok()?;
+            syn::parse2(error_type_path(args, &enum_name, &Generics::default())).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Options and remaining attributes of the method processed by the impl block
+type SelectedMethod = (ErrorsetArgs, Vec<syn::Attribute>);
+
+/// Options and remaining attributes of the methods of the impl block, which are processed, `None` for other items.
+/// Only methods marked with `#[errorset]` are processed unless `all_methods` is set,
+/// in which case every method returning the error tuple is processed.
+fn select_methods(
+    args: &ErrorsetArgs,
+    item_impl: &ItemImpl,
+    impl_ctx: &ImplContext,
+    all_methods: bool,
+) -> Result<Vec<Option<SelectedMethod>>> {
+    let mut selected = Vec::new();
+    for item in &item_impl.items {
+        let syn::ImplItem::Fn(method) = item else {
+            selected.push(None);
+            continue;
+        };
+        let mut new_attrs = Vec::new();
+        let mut marked = false;
+        let mut method_args = args.clone();
+
+        for attr in &method.attrs {
+            if attr.path().is_ident("errorset") {
+                // options of the method override options of the impl block
+                if let syn::Meta::List(_) = attr.meta {
+                    method_args = args.merge(attr.parse_args()?);
                 }
-                Ok(None) => new_items.push(item),
-                Err(e) => return e.to_compile_error().into(),
+                marked = true;
+            } else {
+                new_attrs.push(attr.clone());
             }
-        } else {
+        }
+
+        if !(marked || (all_methods && returns_error_tuple(&method.sig))) {
+            selected.push(None);
+            continue;
+        }
+        if method_args.self_mod && method_args.use_set.is_none() {
+            method_args.module = Some(impl_ctx.self_module(method_args.module.as_ref())?);
+        }
+        selected.push(Some((method_args, new_attrs)));
+    }
+    Ok(selected)
+}
+
+/// Processes methods of the impl block, returning the new impl block and the generated enums
+fn process_impl(
+    args: &ErrorsetArgs,
+    item_impl: ItemImpl,
+    all_methods: bool,
+    functions: &HashMap<String, Type>,
+    module_enums: &ModuleEnums,
+) -> Result<(ItemImpl, Vec<GeneratedEnum>)> {
+    let impl_ctx = ImplContext::new(&item_impl);
+    let selected = select_methods(args, &item_impl, &impl_ctx, all_methods)?;
+
+    // enums of all selected methods are placed into the same modules
+    let mut module_enums = module_enums.clone();
+    for (item, selection) in item_impl.items.iter().zip(&selected) {
+        if let (syn::ImplItem::Fn(method), Some((method_args, _))) = (item, selection) {
+            module_enums.add(method_args, &method.sig.ident, Some(&impl_ctx));
+        }
+    }
+
+    // errors of the selected methods are known to the methods inferring their errors
+    let mut callees = infer::Callees {
+        functions: functions.clone(),
+        methods: HashMap::new(),
+    };
+    for (item, selection) in item_impl.items.iter().zip(&selected) {
+        if let (syn::ImplItem::Fn(method), Some((method_args, _))) = (item, selection) {
+            if let Some(ty) = callee_error_type(method_args, &method.sig, Some(&impl_ctx)) {
+                callees.methods.insert(method.sig.ident.to_string(), ty);
+            }
+        }
+    }
+
+    let mut new_items = Vec::new();
+    let mut new_enums = Vec::new();
+    for (item, selection) in item_impl.items.into_iter().zip(selected) {
+        let (syn::ImplItem::Fn(method), Some((method_args, new_attrs))) = (&item, selection) else {
             new_items.push(item);
+            continue;
+        };
+
+        let item_fn = ItemFn {
+            attrs: new_attrs,
+            vis: method.vis.clone(),
+            sig: method.sig.clone(),
+            block: Box::new(method.block.clone()),
+        };
+
+        match process_fn(
+            &method_args,
+            &item_fn,
+            Some(&impl_ctx),
+            &callees,
+            module_enums.get(&method_args),
+        )? {
+            Some(Output { enum_def, item_def }) => {
+                let impl_item = syn::parse2::<ImplItemFn>(item_def).expect("Invalid method reparse");
+                new_items.push(impl_item.into());
+                new_enums.push(GeneratedEnum::new(&method_args, enum_def));
+            }
+            None => {
+                let ItemFn { attrs, vis, sig, block } = item_fn;
+                let impl_item =
+                    syn::parse2::<ImplItemFn>(quote! { #(#attrs)* #vis #sig #block }).expect("Invalid method reparse");
+                new_items.push(impl_item.into());
+            }
         }
     }
 
-    let new_impl_block = ItemImpl { items: new_items, ..item_impl };
+    Ok((ItemImpl { items: new_items, ..item_impl }, new_enums))
+}
 
-    if let Some(module) = &args.module {
-        // create module if new_enums is not empty
-        // otherwise, just add new_impl_block
-        if new_enums.is_empty() {
-            quote! {
-                #new_impl_block
+/// Rejects `self_prefix` along with the shared module. Each impl block expands separately and defines
+/// its own module, so modules of several impl blocks collide unless the enclosing module is processed.
+fn check_shared_module(args: &ErrorsetArgs, item_impl: &ItemImpl) -> Result<()> {
+    let impl_ctx = ImplContext::new(item_impl);
+    for (method_args, _) in select_methods(args, item_impl, &impl_ctx, false)?.iter().flatten() {
+        if let (true, false, Some(module)) = (method_args.self_prefix, method_args.self_mod, &method_args.module) {
+            return Err(syn::Error::new_spanned(
+                module,
+                "`self_prefix` can not share the module between impl blocks, apply `#[errorset(mod ...)]` \
+                 to the enclosing module instead",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn handle_impl_block(args: &ErrorsetArgs, item_impl: ItemImpl) -> TokenStream {
+    if let Err(e) = check_shared_module(args, &item_impl) {
+        return e.to_compile_error().into();
+    }
+    let (new_impl_block, new_enums) =
+        match process_impl(args, item_impl, false, &HashMap::new(), &ModuleEnums::default()) {
+            Ok(processed) => processed,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+    let enums = place_enums(new_enums);
+    quote! {
+        #enums
+        #new_impl_block
+    }
+    .into()
+}
+
+/// Processes every function, impl method and type alias of the inline module, which uses the error tuple.
+/// Items having their own `#[errorset]` attribute are left as is, so they are expanded with their own options.
+fn process_module(args: &ErrorsetArgs, item_mod: ItemMod) -> Result<ItemMod> {
+    let Some((brace, items)) = item_mod.content else {
+        return Err(syn::Error::new_spanned(
+            &item_mod,
+            "errorset can only be applied to inline modules",
+        ));
+    };
+
+    // errors of the processed functions are known to the functions inferring their errors
+    let mut callees = infer::Callees::default();
+    for item in &items {
+        if let syn::Item::Fn(item_fn) = item {
+            if !has_errorset_attr(&item_fn.attrs) {
+                if let Some(ty) = callee_error_type(args, &item_fn.sig, None) {
+                    callees.functions.insert(item_fn.sig.ident.to_string(), ty);
+                }
             }
-        } else {
-            let vis = &args.visibility;
-            quote! {
-                #vis mod #module {
-                    use super::*;
-                    #(#new_enums)*
+        }
+    }
+
+    // enums of all processed items, including methods, are placed into the same modules
+    let mut module_enums = ModuleEnums::default();
+    for item in &items {
+        match item {
+            syn::Item::Fn(item_fn) if !has_errorset_attr(&item_fn.attrs) && returns_error_tuple(&item_fn.sig) => {
+                module_enums.add(args, &item_fn.sig.ident, None);
+            }
+            syn::Item::Type(item_type) if !has_errorset_attr(&item_type.attrs) && has_error_tuple(&item_type.ty) => {
+                module_enums.add(args, &item_type.ident, None);
+            }
+            syn::Item::Impl(item_impl) if item_impl.trait_.is_none() && !has_errorset_attr(&item_impl.attrs) => {
+                let impl_ctx = ImplContext::new(item_impl);
+                let selected = select_methods(args, item_impl, &impl_ctx, true)?;
+                for (item, selection) in item_impl.items.iter().zip(&selected) {
+                    if let (syn::ImplItem::Fn(method), Some((method_args, _))) = (item, selection) {
+                        module_enums.add(method_args, &method.sig.ident, Some(&impl_ctx));
+                    }
                 }
-                #new_impl_block
             }
+            _ => {}
         }
-    } else {
-        quote! {
-            #(#new_enums)*
-            #new_impl_block
+    }
+
+    let mut new_items = Vec::new();
+    let mut new_enums = Vec::new();
+    for item in items {
+        match item {
+            syn::Item::Fn(item_fn) if !has_errorset_attr(&item_fn.attrs) && returns_error_tuple(&item_fn.sig) => {
+                let Output { enum_def, item_def } = process_fn(args, &item_fn, None, &callees, module_enums.get(args))?
+                    .expect("Error tuple is checked");
+                new_enums.push(GeneratedEnum::new(args, enum_def));
+                new_items.push(syn::Item::Verbatim(item_def));
+            }
+            syn::Item::Type(item_type) if !has_errorset_attr(&item_type.attrs) && has_error_tuple(&item_type.ty) => {
+                let Output { enum_def, item_def } =
+                    process_type_alias(args, &item_type, module_enums.get(args))?.expect("Error tuple is checked");
+                new_enums.push(GeneratedEnum::new(args, enum_def));
+                new_items.push(syn::Item::Verbatim(item_def));
+            }
+            // trait implementations must keep signatures of the trait
+            syn::Item::Impl(item_impl) if item_impl.trait_.is_none() && !has_errorset_attr(&item_impl.attrs) => {
+                let (new_impl, enums) = process_impl(args, item_impl, true, &callees.functions, &module_enums)?;
+                new_enums.extend(enums);
+                new_items.push(syn::Item::Impl(new_impl));
+            }
+            syn::Item::Mod(nested) if nested.content.is_some() && !has_errorset_attr(&nested.attrs) => {
+                new_items.push(syn::Item::Mod(process_module(args, nested)?));
+            }
+            other => new_items.push(other),
         }
     }
+
+    if !new_enums.is_empty() {
+        new_items.push(syn::Item::Verbatim(place_enums(new_enums)));
+    }
+
+    Ok(ItemMod { content: Some((brace, new_items)), ..item_mod })
+}
+
+fn handle_module(args: &ErrorsetArgs, item_mod: ItemMod) -> TokenStream {
+    match process_module(args, item_mod) {
+        Ok(item_mod) => quote! { #item_mod },
+        Err(e) => e.to_compile_error(),
+    }
     .into()
 }
@@ -0,0 +1,140 @@
+//! Rewriting of the error type paths, so they resolve inside the generated enum and its module
+
+use syn::{
+    visit_mut::{self, VisitMut},
+    Generics, Ident, Pat, PatIdent, PathSegment, Result, Type, TypePath,
+};
+
+/// Replaces `Self` with the self type of the impl block, since inside the enum definition it refers to the enum.
+/// Returns an error for paths which can not be resolved outside of the impl block, like `Self::Error`.
+struct SelfType<'a> {
+    self_ty: Option<&'a Type>,
+    errors: Vec<syn::Error>,
+}
+
+impl VisitMut for SelfType<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = ty {
+            if path.is_ident("Self") {
+                match self.self_ty {
+                    Some(self_ty) => *ty = self_ty.clone(),
+                    None => self.errors.push(syn::Error::new_spanned(
+                        &path,
+                        "`Self` can only be used in error types of methods",
+                    )),
+                }
+                return;
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_type_impl_trait_mut(&mut self, ty: &mut syn::TypeImplTrait) {
+        self.errors.push(syn::Error::new_spanned(
+            ty,
+            "`impl Trait` can not be used in error types",
+        ));
+    }
+
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if path.leading_colon.is_none() && path.segments.len() > 1 && path.segments[0].ident == "Self" {
+            self.errors.push(syn::Error::new_spanned(
+                &path,
+                "Associated types of `Self` can not be used in error types, use the full path like `<Foo as Trait>::Error`",
+            ));
+            return;
+        }
+        visit_mut::visit_path_mut(self, path);
+    }
+}
+
+/// Rewrites relative paths to resolve the same way inside the generated module, as they do outside of it:
+/// `self::` becomes `super::`, `super::` becomes `super::super::`, and names shadowed by the items
+/// of the module (generated enums) are prefixed with `super::`.
+struct ModulePaths<'a> {
+    shadowed: &'a [&'a Ident],
+}
+
+impl VisitMut for ModulePaths<'_> {
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if path.leading_colon.is_none() {
+            let first = &path.segments[0].ident;
+            let super_segment = PathSegment::from(Ident::new("super", first.span()));
+            if first == "self" {
+                path.segments[0] = super_segment;
+            } else if first == "super" || (path.segments.len() == 1 && self.shadowed.contains(&first)) {
+                path.segments.insert(0, super_segment);
+            }
+        }
+        visit_mut::visit_path_mut(self, path);
+    }
+}
+
+/// Replaces `Self` in the error types with the self type of the impl block
+pub(crate) fn resolve_self<'a>(
+    err_types: impl IntoIterator<Item = &'a mut Type>,
+    self_ty: Option<&Type>,
+) -> Result<()> {
+    let mut visitor = SelfType { self_ty, errors: Vec::new() };
+    for ty in err_types {
+        visitor.visit_type_mut(ty);
+    }
+    match visitor.errors.into_iter().reduce(|mut errors, error| {
+        errors.combine(error);
+        errors
+    }) {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// Rewrites paths of the error types and generics of the enum placed into the generated module
+pub(crate) fn relocate<'a>(
+    err_types: impl IntoIterator<Item = &'a mut Type>,
+    generics: &mut Generics,
+    shadowed: &[&Ident],
+) {
+    let mut visitor = ModulePaths { shadowed };
+    for ty in err_types {
+        visitor.visit_type_mut(ty);
+    }
+    visitor.visit_generics_mut(generics);
+}
+
+/// Prefixes variants of the enum with the enum name in the pattern of the arm: `Err(Error1(e))` becomes
+/// `Err(Errors::Error1(e))`. Only the pattern of the error is rewritten, payloads of the variants are left as is,
+/// so `Err(Timeout(Timeout(n)))` becomes `Err(Errors::Timeout(Timeout(n)))`.
+pub(crate) fn qualify_variants(pat: &mut Pat, enum_name: &Ident, variants: &[Ident]) {
+    match pat {
+        Pat::Or(or) => or
+            .cases
+            .iter_mut()
+            .for_each(|case| qualify_variants(case, enum_name, variants)),
+        Pat::Paren(paren) => qualify_variants(&mut paren.pat, enum_name, variants),
+        Pat::Ident(PatIdent { subpat: Some((_, subpat)), .. }) => qualify_variants(subpat, enum_name, variants),
+        Pat::TupleStruct(tuple) if tuple.qself.is_none() && tuple.path.is_ident("Err") => {
+            for elem in &mut tuple.elems {
+                qualify_variant(elem, enum_name, variants);
+            }
+        }
+        other => qualify_variant(other, enum_name, variants),
+    }
+}
+
+/// Prefixes the variant of the pattern of the error, without descending into its payload
+fn qualify_variant(pat: &mut Pat, enum_name: &Ident, variants: &[Ident]) {
+    match pat {
+        Pat::Or(or) => or
+            .cases
+            .iter_mut()
+            .for_each(|case| qualify_variant(case, enum_name, variants)),
+        Pat::Paren(paren) => qualify_variant(&mut paren.pat, enum_name, variants),
+        Pat::Ident(PatIdent { subpat: Some((_, subpat)), .. }) => qualify_variant(subpat, enum_name, variants),
+        Pat::TupleStruct(tuple)
+            if tuple.qself.is_none() && variants.iter().any(|variant| tuple.path.is_ident(variant)) =>
+        {
+            tuple.path.segments.insert(0, PathSegment::from(enum_name.clone()));
+        }
+        _ => {}
+    }
+}
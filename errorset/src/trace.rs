@@ -0,0 +1,145 @@
+//! Error sets carrying the trace of functions the error passed through, generated with `trace` option
+
+use crate::{enum_variants, generics, variant};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{punctuated::Punctuated, Generics, Ident, Token, Type, Visibility};
+
+/// Returns the name of the enum with variants of the traced set, like `ReadErrorsKind` for `ReadErrors`
+pub(crate) fn kind_name(name: &Ident) -> Ident {
+    Ident::new(&format!("{name}Kind"), name.span())
+}
+
+/// Returns the struct, which holds the kind of the error, which is the regular enum of the set, and the trace.
+/// Each conversion into the set records the frame of the function, taking the trace of the source if it has one.
+pub(crate) fn traced_definition(
+    derives: &[syn::Path],
+    attrs: &[syn::Meta],
+    vis: &Visibility,
+    name: &Ident,
+    generics: &Generics,
+    err_types: &Punctuated<Type, Token![,]>,
+    function: &str,
+) -> TokenStream {
+    let kind_name = kind_name(name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let kind = quote! { #kind_name #ty_generics };
+    let frame = quote! {
+        ::smarterr::trace::Frame::new(#function, ::core::panic::Location::caller())
+    };
+
+    // `From` is not implemented for types depending on generic parameters, the same as for the enum
+    let from_impls = enum_variants(err_types, generics)
+        .into_iter()
+        .filter(|(_, ty)| !generics::depends_on_params(ty, generics))
+        .map(|(_, ty)| {
+            quote! {
+                impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+                    #[track_caller]
+                    fn from(mut source: #ty) -> Self {
+                        // traced sets take their trace with the inherent method, others get the empty one
+                        #[allow(unused_imports)]
+                        use ::smarterr::trace::NoTrace as _;
+                        let mut trace = source.__take_trace();
+                        trace.push(#frame);
+                        #name { kind: ::core::convert::From::from(source), trace }
+                    }
+                }
+            }
+        });
+
+    let bounded = |bound: TokenStream| {
+        let mut generics = generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#kind: #bound));
+        generics
+    };
+    let debug_generics = bounded(quote!(::core::fmt::Debug));
+    let display_generics = bounded(quote!(::core::fmt::Display));
+    let error_generics = bounded(quote!(::core::error::Error));
+    let (_, _, debug_where_clause) = debug_generics.split_for_impl();
+    let (_, _, display_where_clause) = display_generics.split_for_impl();
+    let (_, _, error_where_clause) = error_generics.split_for_impl();
+    let derive = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+    let kind_doc = format!("Kind of the error of [`{name}`]");
+    let variant = variant::traced_variant(name, generics, &enum_variants(err_types, generics));
+
+    quote! {
+        #derive
+        #(#[#attrs])*
+        #vis struct #name #generics #where_clause {
+            kind: #kind,
+            trace: ::smarterr::trace::Trace,
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #kind_doc]
+            pub fn kind(&self) -> &#kind {
+                &self.kind
+            }
+
+            pub fn into_kind(self) -> #kind {
+                self.kind
+            }
+
+            /// Returns frames of the functions the error passed through
+            pub fn trace(&self) -> &::smarterr::trace::Trace {
+                &self.trace
+            }
+
+            #[doc(hidden)]
+            pub fn __take_trace(&mut self) -> ::smarterr::trace::Trace {
+                ::core::mem::take(&mut self.trace)
+            }
+        }
+
+        impl #impl_generics ::core::ops::Deref for #name #ty_generics #where_clause {
+            type Target = #kind;
+
+            fn deref(&self) -> &Self::Target {
+                &self.kind
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#kind> for #name #ty_generics #where_clause {
+            #[track_caller]
+            fn from(kind: #kind) -> Self {
+                let mut trace = ::smarterr::trace::Trace::default();
+                trace.push(#frame);
+                #name { kind, trace }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #debug_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(stringify!(#name))
+                    .field("kind", &self.kind)
+                    .field("trace", &self.trace)
+                    .finish()
+            }
+        }
+
+        /// Writes the message of the error, `{:#}` adds frames of the trace on separate lines
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #display_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(&self.kind, f)?;
+                if f.alternate() && !self.trace.is_empty() {
+                    ::core::write!(f, "\n{}", self.trace)?;
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl #impl_generics ::core::error::Error for #name #ty_generics #error_where_clause {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                ::core::error::Error::source(&self.kind)
+            }
+        }
+
+        #variant
+
+        #(#from_impls)*
+    }
+}
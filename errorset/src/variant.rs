@@ -0,0 +1,124 @@
+//! Lookups of the error sources, which reach the error held by the variant of the set
+
+use crate::bounded_generics;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, Type};
+
+/// Returns the trait converting the error into the trait object. Types like `anyhow::Error` and `Box<dyn Error>`
+/// are not errors themselves, so they are reached through `Deref`.
+pub(crate) fn as_dyn_error() -> TokenStream {
+    quote! {
+        trait AsDynError<'a> {
+            fn __as_dyn_error(&self) -> &(dyn ::core::error::Error + 'a);
+        }
+        impl<'a, __E: ::core::error::Error + 'a> AsDynError<'a> for __E {
+            fn __as_dyn_error(&self) -> &(dyn ::core::error::Error + 'a) {
+                self
+            }
+        }
+        impl<'a> AsDynError<'a> for dyn ::core::error::Error + 'a {
+            fn __as_dyn_error(&self) -> &(dyn ::core::error::Error + 'a) {
+                self
+            }
+        }
+        impl<'a> AsDynError<'a> for dyn ::core::error::Error + ::core::marker::Send + 'a {
+            fn __as_dyn_error(&self) -> &(dyn ::core::error::Error + 'a) {
+                self
+            }
+        }
+        impl<'a> AsDynError<'a> for dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync + 'a {
+            fn __as_dyn_error(&self) -> &(dyn ::core::error::Error + 'a) {
+                self
+            }
+        }
+    }
+}
+
+/// Returns the lookup methods of the enum. The enum is transparent, its source is the source of the held error,
+/// so the held error is never returned by `source` and is reached with the inherent method `__variant` instead.
+/// Sets held by the variant define `__variant` as well, which takes priority over the fallback trait,
+/// so the error held by the nested set is returned. The fallback relies on `AsDynError` of [`as_dyn_error`],
+/// which is defined along with the enum.
+pub(crate) fn enum_variant(enum_name: &Ident, generics: &Generics, err_types: &[(Ident, &Type)]) -> TokenStream {
+    let arms = err_types.iter().map(|(ty_name, _)| {
+        quote! {
+            Self::#ty_name(ref inner) => inner.__variant(),
+        }
+    });
+    let generics = static_generics(enum_name, generics, err_types);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let lookups = lookups(quote! { self.__variant() });
+    quote! {
+        trait NoVariant {
+            fn __variant(&self) -> &(dyn ::core::error::Error + 'static);
+        }
+        impl<__E: ?::core::marker::Sized + AsDynError<'static>> NoVariant for __E {
+            fn __variant(&self) -> &(dyn ::core::error::Error + 'static) {
+                self.__as_dyn_error()
+            }
+        }
+
+        impl #impl_generics #enum_name #ty_generics #where_clause {
+            #[doc(hidden)]
+            pub fn __variant(&self) -> &(dyn ::core::error::Error + 'static) {
+                match *self {
+                    #(#arms)*
+                }
+            }
+
+            #lookups
+        }
+    }
+}
+
+/// Returns the lookup methods of the traced set, delegating to its kind
+pub(crate) fn traced_variant(name: &Ident, generics: &Generics, err_types: &[(Ident, &Type)]) -> TokenStream {
+    let generics = static_generics(name, generics, err_types);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let lookups = lookups(quote! { self.kind.__variant() });
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc(hidden)]
+            pub fn __variant(&self) -> &(dyn ::core::error::Error + 'static) {
+                self.kind.__variant()
+            }
+
+            #lookups
+        }
+    }
+}
+
+/// Returns `find_source` and `downcast_chain_ref`, which take priority over the methods of `smarterr::ErrorExt`.
+/// Both have the meaning of the trait methods, where the held error is wrapped by the set, but the trait methods
+/// can not reach it, since the set is transparent.
+fn lookups(variant: TokenStream) -> TokenStream {
+    quote! {
+        /// Returns the first error of the given type wrapped by the set, skipping the set itself: the held error
+        /// and its sources. `smarterr::ErrorExt::find_source` applied to the trait object of the set skips
+        /// the held error.
+        pub fn find_source<__T: ::core::error::Error + 'static>(&self) -> ::core::option::Option<&__T> {
+            ::core::iter::successors(::core::option::Option::Some(#variant), |error| error.source())
+                .find_map(|error| error.downcast_ref())
+        }
+
+        /// Returns the set itself, if it's of the given type, or the first error of the given type wrapped by it
+        pub fn downcast_chain_ref<__T: ::core::error::Error + 'static>(&self) -> ::core::option::Option<&__T> {
+            let error: &(dyn ::core::error::Error + 'static) = self;
+            error.downcast_ref().or_else(|| self.find_source())
+        }
+    }
+}
+
+/// Returns generics of the set, where it and the error types mentioning its generic parameters are `'static` errors
+fn static_generics(name: &Ident, generics: &Generics, err_types: &[(Ident, &Type)]) -> Generics {
+    let mut bounded = bounded_generics(generics, err_types, quote!(::core::error::Error + 'static));
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        bounded
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#name #ty_generics: ::core::error::Error + 'static));
+    }
+    bounded
+}
@@ -1,7 +1,7 @@
 use std::num::ParseIntError;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 #[error("The first error [data={data}]")]
 pub struct Error1 {
     pub data: u32,
@@ -9,7 +9,7 @@ pub struct Error1 {
     pub source: ParseIntError,
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 #[error("The second error [msg={message}]")]
 pub struct Error2 {
     pub message: String,
@@ -19,6 +19,25 @@ pub struct Error2 {
 #[error("E3")]
 pub struct Error3;
 
+#[derive(Error, Debug)]
+#[error("Wrapped [value={0:?}]")]
+pub struct Wrapper<T: std::fmt::Debug>(pub T);
+
+#[derive(Error, Debug)]
+#[error("Syntax error [input={input}]")]
+pub struct SyntaxError<'a> {
+    pub input: &'a str,
+}
+
+#[derive(Error, Debug)]
+#[error("Timed out after {0}s")]
+pub struct Timeout(pub u32);
+
+pub trait Source {
+    type Error: std::error::Error + 'static;
+    fn read(&self) -> Result<String, Self::Error>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,6 +58,69 @@ mod tests {
         }
     }
 
+    // Test the enum is transparent: the message and the source are the ones of the variant
+    #[test]
+    fn test_display_and_source() {
+        use std::error::Error;
+
+        let e = errors::ErrorSetErrors::from(Error1 {
+            data: 42,
+            source: "-123".parse::<u32>().unwrap_err(),
+        });
+        assert_eq!(e.to_string(), "The first error [data=42]");
+        assert_eq!(e.source().unwrap().to_string(), "invalid digit found in string");
+        assert_eq!(
+            format!("{:#}", anyhow::anyhow!(e)),
+            "The first error [data=42]: invalid digit found in string"
+        );
+
+        let e = errors::ErrorSetErrors::from(Error2 { message: "msg".to_owned() });
+        assert_eq!(e.to_string(), "The second error [msg=msg]");
+        assert!(e.source().is_none());
+    }
+
+    #[errorset(pub(crate) mod deref_errors)]
+    fn with_anyhow(fail: bool) -> Result<(), (Error2, anyhow::Error)> {
+        if fail {
+            return Err(Error2 { message: "msg".to_owned() }.into());
+        }
+        Err(anyhow::Error::from("-1".parse::<u32>().unwrap_err())
+            .context("parsing")
+            .into())
+    }
+
+    #[errorset(pub(crate) mod boxed_errors)]
+    fn with_boxed() -> Result<(), (Box<dyn std::error::Error + Send + Sync>,)> {
+        let source: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(Error1 { data: 7, source: "-1".parse::<u32>().unwrap_err() });
+        Err(source.into())
+    }
+
+    #[errorset(pub(crate) mod generic_deref_errors)]
+    fn with_generic<T: std::fmt::Debug>(value: T) -> Result<(), (Wrapper<T>, anyhow::Error)> {
+        Err(Wrapper(value).into())
+    }
+
+    // Variants, which are not errors themselves, but deref to one, are transparent as well
+    #[test]
+    fn test_deref_errors() {
+        use std::error::Error;
+
+        let e = with_anyhow(false).unwrap_err();
+        assert!(matches!(e, deref_errors::WithAnyhowErrors::Error(_)));
+        assert_eq!(e.to_string(), "parsing");
+        assert_eq!(e.source().unwrap().to_string(), "invalid digit found in string");
+        assert!(with_anyhow(true).unwrap_err().source().is_none());
+
+        let e = with_generic(1).unwrap_err();
+        assert_eq!(e.to_string(), "Wrapped [value=1]");
+        assert!(e.source().is_none());
+
+        let e = with_boxed().unwrap_err();
+        assert_eq!(e.to_string(), "The first error [data=7]");
+        assert_eq!(e.source().unwrap().to_string(), "invalid digit found in string");
+    }
+
     struct _SomeStruct {}
 
     #[errorset(pub(crate) mod eei)]
@@ -85,6 +167,7 @@ mod tests {
     }
 
     // Test where-clause is preserved
+    #[allow(clippy::multiple_bound_locations)]
     #[errorset(pub(crate) mod where_mod)]
     fn with_where<T: AsRef<str>>(x: T) -> Result<(), (Error1, Error2)>
     where
@@ -135,4 +218,597 @@ mod tests {
             _ => panic!("Expected Error1"),
         }
     }
+
+    // Test additional derives and attributes of the generated enum
+    #[errorset(pub(crate) mod derive_mod, derive(Clone, PartialEq), attr(must_use, doc = "Cloneable errors"))]
+    fn cloneable() -> Result<(), (Error1, Error2)> {
+        Err(Error2 { message: "clone me".to_owned() }.into())
+    }
+
+    #[test]
+    fn test_derive_and_attrs() {
+        let err = cloneable().unwrap_err();
+        assert_eq!(err.clone(), err);
+        assert!(matches!(err, derive_mod::CloneableErrors::Error2(e) if e.message == "clone me"));
+    }
+
+    // Test exact enum name, naming template and explicit enum visibility
+    #[errorset(name = LoadError, vis = pub(crate))]
+    fn load() -> Result<(), (Error1, Error2)> {
+        Err(Error2 { message: "not loaded".to_owned() }.into())
+    }
+
+    #[errorset(pub(crate) mod named_mod, name = "{}Error", vis = pub(super))]
+    fn store() -> Result<(), (Error1, Error2)> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_naming() {
+        let _: Result<(), LoadError> = load();
+        let _: Result<(), named_mod::StoreError> = store();
+        assert!(matches!(load(), Err(LoadError::Error2(_))));
+    }
+
+    // Test named sets shared by several functions
+    errorset::set!(pub(crate) ApiErrors = (Error1, Error2));
+
+    #[errorset(use = ApiErrors)]
+    fn api_one() -> Result<u32, (Error1, Error2)> {
+        Err(Error2 { message: "api one".to_owned() }.into())
+    }
+
+    #[errorset(use = ApiErrors)]
+    impl _SomeStruct {
+        #[errorset]
+        fn api_two(&self) -> Result<u32, (Error2, Error1)> {
+            Ok(2)
+        }
+    }
+
+    #[test]
+    fn test_named_set() {
+        let results: [Result<u32, ApiErrors>; 2] = [api_one(), _SomeStruct {}.api_two()];
+        assert!(matches!(&results[0], Err(ApiErrors::Error2(e)) if e.message == "api one"));
+        assert_eq!(results[1].as_ref().unwrap(), &2);
+    }
+
+    // Test type aliases
+    #[errorset(pub(crate) mod alias_mod)]
+    pub(crate) type LoadResult<T> = Result<T, (Error1, Error2)>;
+
+    fn load_value(value: &str) -> LoadResult<u32> {
+        let value = value.parse::<u32>().map_err(|source| Error1 { data: 3, source })?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_type_alias() {
+        assert_eq!(load_value("12").unwrap(), 12);
+        match load_value("twelve") {
+            Err(alias_mod::LoadResultErrors::Error1(e)) => assert_eq!(e.data, 3),
+            _ => panic!("Expected Error1"),
+        }
+    }
+
+    // Test all functions and methods of the module are processed at once
+    #[errorset(pub(crate) mod errors)]
+    mod storage {
+        use super::*;
+
+        pub struct Storage;
+
+        impl Storage {
+            pub fn read(&self) -> Result<u32, (Error1, Error2)> {
+                Err(Error2 { message: "read".to_owned() }.into())
+            }
+
+            pub fn size(&self) -> usize {
+                0
+            }
+        }
+
+        pub fn write() -> Result<(), (Error2,)> {
+            Err(Error2 { message: "write".to_owned() }.into())
+        }
+
+        pub fn plain() -> Result<(), Error2> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_module() {
+        assert!(matches!(
+            storage::Storage.read(),
+            Err(storage::errors::ReadErrors::Error2(_))
+        ));
+        assert!(matches!(storage::write(), Err(storage::errors::WriteErrors::Error2(_))));
+        assert_eq!(storage::Storage.size(), 0);
+        assert!(storage::plain().is_ok());
+    }
+
+    // Test options of the method override options of the impl block
+    struct _OtherStruct {}
+
+    #[errorset(pub(crate) mod other_errors, derive(Clone))]
+    impl _OtherStruct {
+        #[errorset]
+        fn first(&self) -> Result<(), (Error1, Error2)> {
+            Ok(())
+        }
+
+        #[errorset(name = OtherSecondError, derive(PartialEq))]
+        fn second(&self) -> Result<(), (Error1, Error2)> {
+            Err(Error2 { message: "second".to_owned() }.into())
+        }
+
+        #[errorset(pub(crate) mod other_errors_ext)]
+        fn third(&self) -> Result<(), (Error2,)> {
+            Ok(())
+        }
+
+        #[errorset(use = ApiErrors)]
+        fn fourth(&self) -> Result<(), (Error1, Error2)> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_method_options() {
+        let s = _OtherStruct {};
+        let _: Result<(), other_errors::FirstErrors> = s.first().clone();
+        let second: Result<(), other_errors::OtherSecondError> = s.second();
+        assert_eq!(second.clone(), second);
+        let _: Result<(), other_errors_ext::ThirdErrors> = s.third();
+        let _: Result<(), ApiErrors> = s.fourth();
+    }
+
+    // Test enums of methods with the same name in different impl blocks do not collide
+    #[errorset(pub(crate) mod ctor_errors)]
+    mod ctors {
+        use super::{Error1, Error2};
+
+        pub(crate) struct Foo;
+        pub(crate) struct Qux;
+        pub(crate) struct Bar<T>(pub(crate) T);
+        pub(crate) struct Baz;
+
+        impl Foo {
+            #[errorset(self_prefix)]
+            pub(crate) fn new() -> Result<Self, (Error1, Error2)> {
+                Ok(Foo)
+            }
+        }
+
+        impl Qux {
+            #[errorset(self_prefix)]
+            pub(crate) fn new() -> Result<Self, (Error1, Error2)> {
+                Ok(Qux)
+            }
+        }
+
+        impl<T> Bar<T> {
+            #[errorset(self_mod)]
+            pub(crate) fn new(value: T) -> Result<Self, (Error1, Error2)> {
+                Ok(Bar(value))
+            }
+        }
+
+        impl Baz {
+            #[errorset(self_mod)]
+            pub(crate) fn new() -> Result<Self, (Error1, Error2)> {
+                Ok(Baz)
+            }
+        }
+
+        impl Baz {
+            #[errorset(self_mod)]
+            pub(crate) fn reset(&self) -> Result<(), (Error1, Error2)> {
+                Ok(())
+            }
+        }
+    }
+
+    // Without the shared module enums are prefixed in the scope of the impl block
+    struct Quux;
+
+    #[errorset(self_prefix)]
+    impl Quux {
+        #[errorset]
+        fn new() -> Result<Self, (Error1, Error2)> {
+            Ok(Quux)
+        }
+    }
+
+    #[test]
+    fn test_self_prefix_and_mod() {
+        use ctors::*;
+        let _: Result<Foo, ctors::ctor_errors::FooNewErrors> = Foo::new();
+        let _: Result<Qux, ctors::ctor_errors::QuxNewErrors> = Qux::new();
+        let _: Result<Bar<u8>, ctors::bar_ctor_errors::NewErrors> = Bar::new(1);
+        let baz: Result<Baz, ctors::baz_ctor_errors::NewErrors> = Baz::new();
+        let _: Result<(), ctors::baz_ctor_errors::ResetErrors> = baz.ok().unwrap().reset();
+        let _: Result<Quux, QuuxNewErrors> = Quux::new();
+    }
+
+    // Test error types using generic parameters
+    struct FailingSource;
+
+    impl Source for FailingSource {
+        type Error = Error2;
+        fn read(&self) -> Result<String, Self::Error> {
+            Err(Error2 { message: "source".to_owned() })
+        }
+    }
+
+    #[errorset(pub(crate) mod generic_mod)]
+    fn read_number<S: Source, D>(source: &S, _default: D) -> Result<u32, (S::Error, Error1)> {
+        let data = source.read().map_err(generic_mod::ReadNumberErrors::SError)?;
+        Ok(data.parse::<u32>().map_err(|source| Error1 { data: 0, source })?)
+    }
+
+    struct Holder<T>(T);
+
+    #[errorset(pub(crate) mod holder_mod)]
+    impl<T> Holder<T>
+    where
+        T: std::fmt::Debug + Clone,
+    {
+        #[errorset]
+        fn check(&self) -> Result<(), (Wrapper<T>, Error2)> {
+            Err(Wrapper(self.0.clone()).into())
+        }
+    }
+
+    #[test]
+    fn test_generic_errors() {
+        match read_number(&FailingSource, ()) {
+            Err(generic_mod::ReadNumberErrors::SError(e)) => assert_eq!(e.message, "source"),
+            _ => panic!("Expected the error of the source"),
+        }
+        match Holder(5u8).check() {
+            Err(holder_mod::CheckErrors::Wrapper(e)) => assert_eq!(e.to_string(), "Wrapped [value=5]"),
+            _ => panic!("Expected Wrapper"),
+        }
+    }
+
+    // Test error types borrowing the input
+    #[errorset(pub(crate) mod parse_mod)]
+    fn parse_flag<'a, 'b: 'a>(input: &'a str, _context: &'b str) -> Result<bool, (SyntaxError<'a>, Error2)> {
+        match input {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err(SyntaxError { input }.into()),
+        }
+    }
+
+    #[test]
+    fn test_lifetimes() {
+        let input = String::from("maybe");
+        match parse_flag(&input, "") {
+            Err(parse_mod::ParseFlagErrors::SyntaxError(e)) => assert_eq!(e.input, "maybe"),
+            _ => panic!("Expected SyntaxError"),
+        }
+        assert!(parse_flag("yes", "").unwrap());
+    }
+
+    // Test references to error types, named after the referenced type
+    #[errorset(pub(crate) mod ref_mod)]
+    fn check_cached<'a>(cached: Option<&'a Error2>) -> Result<u32, (&'a Error2, Error1)> {
+        match cached {
+            Some(error) => Err(error.into()),
+            None => Ok(0),
+        }
+    }
+
+    #[test]
+    fn test_reference_errors() {
+        let error = Error2 { message: "cached".to_owned() };
+        match check_cached(Some(&error)) {
+            Err(ref_mod::CheckCachedErrors::Error2(e)) => assert_eq!(e.message, "cached"),
+            _ => panic!("Expected Error2"),
+        }
+        assert_eq!(check_cached(None).unwrap(), 0);
+    }
+
+    // Test inline error definitions of the tuple
+    #[errorset(pub(crate) mod inline_mod)]
+    fn parse_port(
+        data: &str,
+    ) -> Result<
+        u16,
+        (
+            error!(InvalidPort<std::num::ParseIntError> { pub data: String } => "Invalid port [data={data}]: {source}"),
+            error!(ReservedPort [location] { pub port: u16 } => "Port {port} is reserved [at {location}]"),
+            Error2,
+        ),
+    > {
+        let port = data
+            .parse::<u16>()
+            .map_err(|source| inline_mod::InvalidPort { data: data.to_owned(), source })?;
+        match port {
+            0 => Err(Error2 { message: "zero".to_owned() }.into()),
+            1..1024 => Err(inline_mod::ReservedPort::new(port).into()),
+            _ => Ok(port),
+        }
+    }
+
+    #[test]
+    fn test_inline_errors() {
+        use std::error::Error;
+
+        let e = parse_port("x").unwrap_err();
+        assert!(matches!(e, inline_mod::ParsePortErrors::InvalidPort(_)));
+        assert_eq!(e.to_string(), "Invalid port [data=x]: invalid digit found in string");
+        assert_eq!(e.source().unwrap().to_string(), "invalid digit found in string");
+        match parse_port("80") {
+            Err(inline_mod::ParsePortErrors::ReservedPort(e)) => {
+                assert_eq!(e.location.file(), file!());
+                assert_eq!(e.to_string(), format!("Port 80 is reserved [at {}]", e.location));
+            }
+            other => panic!("Expected ReservedPort, got {other:?}"),
+        }
+        assert!(matches!(parse_port("0"), Err(inline_mod::ParsePortErrors::Error2(_))));
+        assert_eq!(parse_port("8080").unwrap(), 8080);
+    }
+
+    // Test inline errors wrapping errors, which deref to the trait object, and fields without visibility
+    #[errorset(pub(crate) mod wrapped_mod)]
+    fn load_wrapped(data: &str) -> Result<u32, (error!(Wrapped<anyhow::Error> { data: String } => "Wrapped {data}"),)> {
+        data.parse::<u32>()
+            .map_err(|e| wrapped_mod::Wrapped { data: data.to_owned(), source: e.into() }.into())
+    }
+
+    #[test]
+    fn test_inline_deref_errors() {
+        use std::error::Error;
+
+        let e = load_wrapped("x").unwrap_err();
+        assert_eq!(e.to_string(), "Wrapped x");
+        let wrapped_mod::LoadWrappedErrors::Wrapped(wrapped) = &e;
+        assert_eq!(wrapped.data, "x");
+        assert_eq!(wrapped.source().unwrap().to_string(), "invalid digit found in string");
+    }
+
+    // Test error types inferred from the function body
+    #[errorset(pub(crate) mod errors)]
+    mod inventory {
+        use super::*;
+
+        pub fn load(data: &str) -> Result<u32, (Error1,)> {
+            Ok(data.parse::<u32>().map_err(|source| Error1 { data: 1, source })?)
+        }
+
+        pub fn total(data: &str) -> Result<u32, _> {
+            let count = load(data)?;
+            if count == 0 {
+                return Err(Error2 { message: "empty".to_owned() }.into());
+            }
+            Ok(count)
+        }
+
+        pub struct Shelf;
+
+        impl Shelf {
+            pub fn check(&self, data: &str) -> Result<u32, _> {
+                if data.is_empty() {
+                    return Err(Error3.into());
+                }
+                Ok(load(data)?)
+            }
+        }
+    }
+
+    #[errorset]
+    fn checked_total(data: &str, limit: u32) -> Result<u32, _> {
+        let total = inventory::total(data).map_err(|_| Error3)?;
+        if total > limit {
+            Err(Error2 { message: "limit".to_owned() }.into())
+        } else {
+            Ok(total)
+        }
+    }
+
+    #[test]
+    fn test_infer() {
+        use inventory::errors::TotalErrors;
+
+        assert!(matches!(inventory::total("x"), Err(TotalErrors::LoadErrors(_))));
+        assert!(matches!(inventory::total("0"), Err(TotalErrors::Error2(_))));
+        assert_eq!(inventory::total("5").unwrap(), 5);
+        assert!(matches!(
+            inventory::Shelf.check(""),
+            Err(inventory::errors::CheckErrors::Error3(_))
+        ));
+        assert!(matches!(
+            inventory::Shelf.check("x"),
+            Err(inventory::errors::CheckErrors::LoadErrors(_))
+        ));
+        assert!(matches!(checked_total("x", 10), Err(CheckedTotalErrors::Error3(_))));
+        assert!(matches!(checked_total("50", 10), Err(CheckedTotalErrors::Error2(_))));
+    }
+
+    // Test the lint of error types, which are never produced by the body
+    #[errorset(pub(crate) mod lint_mod, lint)]
+    fn checked_port(data: &str) -> Result<u16, (Error2, Error3)> {
+        if data.is_empty() {
+            return Err(Error3.into());
+        }
+        data.parse::<u16>()
+            .map_err(|_| Error2 { message: data.to_owned() }.into())
+    }
+
+    // Error2 is never produced, the warning is silenced
+    #[allow(deprecated)]
+    #[errorset(pub(crate) mod stale_mod, lint)]
+    fn stale_port(data: &str) -> Result<u16, (Error2, Error3)> {
+        data.parse::<u16>().map_err(|_| Error3.into())
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(matches!(checked_port(""), Err(lint_mod::CheckedPortErrors::Error3(_))));
+        assert!(matches!(checked_port("x"), Err(lint_mod::CheckedPortErrors::Error2(_))));
+        assert!(matches!(stale_port("x"), Err(stale_mod::StalePortErrors::Error3(_))));
+    }
+
+    // Test the block with its own error set
+    fn port_or_default(data: &str) -> u16 {
+        errorset::block!((Error1, error!(Reserved { port: u16 } => "Port {port} is reserved")) => {
+            let port = data.parse::<u32>().map_err(|source| Error1 { data: 0, source })?;
+            if port < 1024 {
+                return Err(Reserved { port: port as u16 }.into());
+            }
+            Ok(port as u16)
+        } match {
+            Ok(port) => port,
+            Err(Error1(_)) => 8080,
+            Err(Reserved(e)) if e.port == 80 => 8080,
+            Err(Reserved(_)) => 1024,
+        })
+    }
+
+    // Only the variant is qualified, the pattern of its payload is left as is
+    fn timeout_secs(secs: u32) -> u32 {
+        errorset::block!((Timeout, Error3) => {
+            if secs > 60 {
+                return Err(Timeout(secs).into());
+            }
+            Err(Error3.into())
+        } match {
+            Ok(()) => 0,
+            Err(Timeout(Timeout(n))) => n,
+            Err(e @ Error3(_)) => e.to_string().len() as u32,
+        })
+    }
+
+    #[test]
+    fn test_block() {
+        assert_eq!(port_or_default("3000"), 3000);
+        assert_eq!(port_or_default("x"), 8080);
+        assert_eq!(port_or_default("80"), 8080);
+        assert_eq!(port_or_default("22"), 1024);
+        assert_eq!(timeout_secs(90), 90);
+        assert_eq!(timeout_secs(30), 2);
+
+        let result = errorset::block!((Error2, Error3) => {
+            Err(Error3.into())
+        });
+        let result: Result<(), _> = result;
+        assert_eq!(result.unwrap_err().to_string(), "E3");
+    }
+
+    // Test backtraces of the variants
+    #[errorset(pub(crate) mod backtrace_mod, backtrace)]
+    fn open_port(
+        data: &str,
+    ) -> Result<
+        u16,
+        (
+            error!(ClosedPort [backtrace] { pub port: u16 } => "Port {port} is closed"),
+            Error2,
+        ),
+    > {
+        match data.parse::<u16>() {
+            Ok(port) if port % 2 == 0 => Ok(port),
+            Ok(port) => Err(backtrace_mod::ClosedPort::new(port).into()),
+            Err(_) => Err(Error2 { message: data.to_owned() }.into()),
+        }
+    }
+
+    #[errorset(pub(crate) mod nested_backtrace_mod, backtrace)]
+    fn open_ports(data: &str) -> Result<Vec<u16>, (backtrace_mod::OpenPortErrors, Error3)> {
+        if data.is_empty() {
+            return Err(Error3.into());
+        }
+        data.split(',').map(|port| Ok(open_port(port)?)).collect()
+    }
+
+    #[test]
+    fn test_backtrace() {
+        use std::backtrace::BacktraceStatus;
+
+        assert_eq!(open_ports("80,8080").unwrap(), [80, 8080]);
+        // the backtrace is returned only if it was captured, depending on `RUST_BACKTRACE`
+        let e = open_port("81").unwrap_err();
+        let backtrace_mod::OpenPortErrors::ClosedPort(closed) = &e else {
+            panic!("Expected ClosedPort");
+        };
+        let captured = closed.backtrace.status() == BacktraceStatus::Captured;
+        assert_eq!(e.backtrace().is_some(), captured);
+        assert_eq!(open_ports("80,81").unwrap_err().backtrace().is_some(), captured);
+        assert!(open_ports("80,x").unwrap_err().backtrace().is_none());
+        assert!(open_ports("").unwrap_err().backtrace().is_none());
+    }
+
+    // Test relative paths of the error types, when the enum is placed into the module
+    mod lookup {
+        use errorset::errorset;
+        use thiserror::Error;
+
+        #[derive(Error, Debug)]
+        #[error("Missing key")]
+        pub struct Missing;
+
+        #[derive(Error, Debug)]
+        #[error("Lookup failed")]
+        pub struct LookupErrors;
+
+        pub struct Table;
+
+        #[errorset(pub(crate) mod errors)]
+        impl Table {
+            #[errorset]
+            pub fn get(&self, key: &str) -> Result<u32, (self::Missing, super::Error2, Self)> {
+                match key {
+                    "" => Err(super::Error2 { message: "empty".to_owned() }.into()),
+                    "table" => Err(Table.into()),
+                    _ => Err(Missing.into()),
+                }
+            }
+
+            #[errorset]
+            pub fn lookup(&self) -> Result<u32, (LookupErrors,)> {
+                Err(LookupErrors.into())
+            }
+
+            // `LookupErrors` is the user type, not the enum of `lookup` placed into the same module
+            #[errorset]
+            pub fn fetch(&self) -> Result<u32, (LookupErrors,)> {
+                Err(LookupErrors.into())
+            }
+        }
+
+        impl std::fmt::Debug for Table {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Table")
+            }
+        }
+
+        impl std::fmt::Display for Table {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Table failed")
+            }
+        }
+
+        impl std::error::Error for Table {}
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        use lookup::errors::{GetErrors, LookupErrors};
+
+        assert!(matches!(lookup::Table.get("key"), Err(GetErrors::Missing(_))));
+        assert!(matches!(lookup::Table.get(""), Err(GetErrors::Error2(_))));
+        assert!(matches!(lookup::Table.get("table"), Err(GetErrors::Table(_))));
+        assert!(matches!(
+            lookup::Table.lookup(),
+            Err(LookupErrors::LookupErrors(lookup::LookupErrors))
+        ));
+        assert!(matches!(
+            lookup::Table.fetch(),
+            Err(lookup::errors::FetchErrors::LookupErrors(lookup::LookupErrors))
+        ));
+    }
 }
@@ -0,0 +1,106 @@
+//! Iteration over the error and its sources, like `error.chain()` or `error.find_source::<ParseIntError>()`
+
+use core::error::Error;
+
+/// Iterator over the error and its sources, starting from the error itself.
+///
+/// Sets generated by `errorset`, `OneOf` and `WithContext` are transparent: their `source` is the source of the
+/// held error, so each of them is a single link of the chain, displayed as the held error and followed
+/// by its sources. The error held by the set generated by `errorset` is wrapped by the set, but it is not the link
+/// of the chain, so it is looked at only by the inherent `find_source` and `downcast_chain_ref` of the set,
+/// which take priority over the ones of `ErrorExt` when the type of the set is known.
+#[derive(Clone)]
+pub struct Chain<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> Chain<'a> {
+    pub fn new(error: &'a (dyn Error + 'static)) -> Self {
+        Chain { next: Some(error) }
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let error = self.next?;
+        self.next = error.source();
+        Some(error)
+    }
+}
+
+/// Utilities over the chain of the error sources, implemented for all errors and error trait objects
+///
+/// ```rust
+/// use smarterr::ErrorExt;
+/// use std::num::ParseIntError;
+///
+/// #[derive(Debug)]
+/// struct InvalidData(ParseIntError);
+///
+/// impl std::fmt::Display for InvalidData {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         f.write_str("Invalid data")
+///     }
+/// }
+///
+/// impl std::error::Error for InvalidData {
+///     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// let error = InvalidData("x".parse::<u32>().unwrap_err());
+/// let messages = error.chain().map(|e| e.to_string()).collect::<Vec<_>>();
+/// assert_eq!(messages, ["Invalid data", "invalid digit found in string"]);
+/// assert!(error.find_source::<ParseIntError>().is_some());
+/// assert_eq!(error.root_cause().to_string(), "invalid digit found in string");
+/// ```
+pub trait ErrorExt {
+    /// Returns the iterator over the error and its sources
+    fn chain(&self) -> Chain<'_>;
+
+    /// Returns the last source of the chain, which is the error itself if it has no source
+    fn root_cause(&self) -> &(dyn Error + 'static) {
+        // the chain always contains the error itself
+        self.chain().last().unwrap()
+    }
+
+    /// Returns the first error of the type `T` wrapped by the error, skipping the error itself. Wrapped errors are
+    /// its sources, and the held error for sets generated by `errorset`, which is found only when the type
+    /// of the set is known. Through the trait object or `E: ErrorExt` the sources of the held error are looked at.
+    fn find_source<T: Error + 'static>(&self) -> Option<&T> {
+        self.chain().skip(1).find_map(|error| error.downcast_ref())
+    }
+
+    /// Returns the error itself, if it's of the type `T`, or the first error of the type `T` wrapped by it,
+    /// the same as `find_source` does
+    fn downcast_chain_ref<T: Error + 'static>(&self) -> Option<&T> {
+        self.chain().find_map(|error| error.downcast_ref())
+    }
+}
+
+impl<E: Error + 'static> ErrorExt for E {
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+}
+
+impl ErrorExt for dyn Error + 'static {
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+}
+
+impl ErrorExt for dyn Error + Send + 'static {
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+}
+
+impl ErrorExt for dyn Error + Send + Sync + 'static {
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+}
@@ -0,0 +1,187 @@
+//! Context attached to typed errors, like `load().context("loading config")`, keeping the error matchable
+
+use crate::chain::{Chain, ErrorExt};
+use alloc::{boxed::Box, string::String, string::ToString, vec::Vec};
+use core::{error::Error, fmt, ops::Deref};
+
+/// Error along with the context describing what was being done when it occurred.
+///
+/// Unlike `anyhow::Context` the type of the error is kept, so variants of the set are matched through `Deref`,
+/// like `match *error { LoadErrors::NotFound(_) => .. }`. `{}` writes the latest context, `{:#}` writes
+/// all contexts, starting from the latest one, followed by the error and its sources, separated by `: `.
+///
+/// `WithContext` does not implement `Error`, so the context added to `Result<T, WithContext<E>>` is pushed
+/// to the same stack. It is converted into `Box<dyn Error>` with `?` or `.into()`.
+///
+/// ```rust
+/// use smarterr::ResultExt;
+///
+/// let error = "x".parse::<u32>().context("parsing the port").context("loading config").unwrap_err();
+/// assert_eq!(error.to_string(), "loading config");
+/// assert_eq!(format!("{error:#}"), "loading config: parsing the port: invalid digit found in string");
+/// assert_eq!(error.error().to_string(), "invalid digit found in string");
+/// ```
+pub struct WithContext<E> {
+    error: E,
+    /// Contexts in the order they were added, so the latest one is the last
+    contexts: Vec<String>,
+}
+
+impl<E> WithContext<E> {
+    /// Creates the error without any context
+    pub fn new(error: E) -> Self {
+        WithContext { error, contexts: Vec::new() }
+    }
+
+    /// Adds the context on top of the existing ones
+    pub fn context<C: fmt::Display>(mut self, context: C) -> Self {
+        self.contexts.push(context.to_string());
+        self
+    }
+
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    pub fn into_error(self) -> E {
+        self.error
+    }
+
+    /// Returns contexts starting from the latest one
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.contexts.iter().rev().map(String::as_str)
+    }
+
+    /// Converts the error, keeping the contexts, like `e.map(LoadErrors::from)`
+    pub fn map<F, M: FnOnce(E) -> F>(self, map: M) -> WithContext<F> {
+        WithContext { error: map(self.error), contexts: self.contexts }
+    }
+}
+
+impl<E> From<E> for WithContext<E> {
+    fn from(error: E) -> Self {
+        WithContext::new(error)
+    }
+}
+
+impl<E> Deref for WithContext<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.error
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for WithContext<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithContext")
+            .field("error", &self.error)
+            .field("contexts", &self.contexts().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<E: Error> fmt::Display for WithContext<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !f.alternate() {
+            return match self.contexts.last() {
+                Some(context) => f.write_str(context),
+                None => fmt::Display::fmt(&self.error, f),
+            };
+        }
+        for context in self.contexts() {
+            write!(f, "{context}: ")?;
+        }
+        write!(f, "{}", self.error)?;
+        let mut source = self.error.source();
+        while let Some(error) = source {
+            write!(f, ": {error}")?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
+/// The chain starts from the error, since contexts are not errors
+impl<E: Error + 'static> ErrorExt for WithContext<E> {
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(&self.error)
+    }
+}
+
+/// `WithContext` converted into the trait object: the message is contexts, starting from the latest one,
+/// and the source is the error
+struct ContextError<E>(WithContext<E>);
+
+impl<E: fmt::Debug> fmt::Debug for ContextError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> fmt::Display for ContextError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, context) in self.0.contexts().enumerate() {
+            if i > 0 {
+                f.write_str(": ")?;
+            }
+            f.write_str(context)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: Error + 'static> Error for ContextError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0.error)
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> From<WithContext<E>> for Box<dyn Error + Send + Sync> {
+    fn from(error: WithContext<E>) -> Self {
+        if error.contexts.is_empty() {
+            Box::new(error.error)
+        } else {
+            Box::new(ContextError(error))
+        }
+    }
+}
+
+impl<E: Error + 'static> From<WithContext<E>> for Box<dyn Error> {
+    fn from(error: WithContext<E>) -> Self {
+        if error.contexts.is_empty() {
+            Box::new(error.error)
+        } else {
+            Box::new(ContextError(error))
+        }
+    }
+}
+
+/// Adds the context to the error of the `Result`
+pub trait ResultExt<T, E> {
+    /// Adds the context to the error, like `.context("loading config")`
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, WithContext<E>>;
+
+    /// Adds the context created only if there is an error, like `.with_context(|| format!("reading {path}"))`
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T, WithContext<E>>;
+}
+
+impl<T, E: Error> ResultExt<T, E> for Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, WithContext<E>> {
+        self.map_err(|error| WithContext::new(error).context(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T, WithContext<E>> {
+        self.map_err(|error| WithContext::new(error).context(context()))
+    }
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, WithContext<E>> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, WithContext<E>> {
+        self.map_err(|error| error.context(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T, WithContext<E>> {
+        self.map_err(|error| error.context(context()))
+    }
+}
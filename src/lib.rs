@@ -1,15 +1,154 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![doc = include_str!("../README.md")]
 
-use std::{rc::Rc, sync::Arc};
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, rc::Rc, string::String};
+use core::panic::Location;
+
+pub mod chain;
+#[cfg(feature = "alloc")]
+pub mod context;
+pub mod one_of;
+#[cfg(feature = "alloc")]
+pub mod report;
+#[cfg(feature = "alloc")]
+pub mod trace;
+
+pub use chain::{Chain, ErrorExt};
+#[cfg(feature = "alloc")]
+pub use context::{ResultExt, WithContext};
 #[cfg(feature = "errorset")]
 pub use errorset;
+pub use one_of::OneOf;
+#[cfg(feature = "alloc")]
+pub use report::Report;
+#[cfg(feature = "alloc")]
+pub use trace::Trace;
 
 #[cfg(feature = "atomic_error")]
 #[macro_export]
 macro_rules! error {
-    ($evis:vis $name:ident<$source:ty> { $($vis:vis $field:ident: $ty:ty),* $(,)? } => $msg:literal) => {
-        #[derive(thiserror::Error, Debug)]
+    // markers in brackets add fields filled by the constructor `new` and methods of the error:
+    // `location` - location of the code, which created the error
+    (@markers [location $($marker:ident)*] [$($extra:tt)*] [$($methods:tt)*] $($rest:tt)*) => {
+        $crate::error!(@markers [$($marker)*]
+            [$($extra)* location: &'static ::core::panic::Location<'static> = ::core::panic::Location::caller(),]
+            [$($methods)*]
+            $($rest)*
+        );
+    };
+    // `backtrace` - backtrace captured when the error is created, if it's enabled with `RUST_BACKTRACE`
+    (@markers [backtrace $($marker:ident)*] [$($extra:tt)*] [$($methods:tt)*] $($rest:tt)*) => {
+        $crate::error!(@markers [$($marker)*]
+            [$($extra)* backtrace: $crate::__private::Backtrace = $crate::__private::Backtrace::capture(),]
+            [$($methods)*
+                #[doc(hidden)]
+                pub fn __backtrace(&self) -> ::core::option::Option<&$crate::__private::Backtrace> {
+                    // disabled backtraces are not reported, so `RUST_BACKTRACE` is respected
+                    match self.backtrace.status() {
+                        $crate::__private::BacktraceStatus::Captured => ::core::option::Option::Some(&self.backtrace),
+                        _ => ::core::option::Option::None,
+                    }
+                }
+            ]
+            $($rest)*
+        );
+    };
+    (@markers [$unknown:ident $($marker:ident)*] $($rest:tt)*) => {
+        ::core::compile_error!(concat!("Unknown marker of the error: `", stringify!($unknown), "`"));
+    };
+    (@markers [] [] [] $($rest:tt)*) => {
+        $crate::__error_struct!($($rest)*);
+    };
+    (@markers [] [$($extra:ident: $extra_ty:ty = $init:expr,)+] [$($methods:tt)*] #[$doc:meta] $evis:vis $name:ident [$($source:ty)?] { $($vis:vis $field:ident: $ty:ty),* } => $msg:literal) => {
+        $crate::__error_struct!(#[$doc] $evis $name [$($source)?] { $($vis $field: $ty,)* $(pub $extra: $extra_ty),* } => $msg);
+
+        impl $name {
+            /// Creates the error, filling fields of the markers
+            #[track_caller]
+            #[allow(clippy::too_many_arguments)]
+            $evis fn new($($field: $ty,)* $(source: $source)?) -> Self {
+                $name { $($field,)* $(source: <$source as ::core::convert::From<$source>>::from(source),)? $($extra: $init,)* }
+            }
+
+            $($methods)*
+        }
+    };
+    ($evis:vis $name:ident $(<$source:ty>)? $([$($marker:ident),* $(,)?])? { $($vis:vis $field:ident: $ty:ty),* $(,)? } => $msg:literal) => {
+        $crate::error!(@markers [$($($marker)*)?] [] []
+            #[doc = concat!("Error with the message `", $msg, "`.")]
+            $evis $name [$($source)?] { $($vis $field: $ty),* } => $msg
+        );
+    };
+}
+
+/// Defines the error struct for `error!`, implementing `Display` and `Error` directly
+#[cfg(all(feature = "atomic_error", not(feature = "thiserror")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __error_struct {
+    (#[$doc:meta] $evis:vis $name:ident [$source:ty] { $($vis:vis $field:ident: $ty:ty),* } => $msg:literal) => {
+        #[$doc]
+        #[derive(Debug)]
+        $evis struct $name {
+            $(
+                $vis $field: $ty,
+            )*
+            pub source: $source,
+        }
+
+        impl ::core::fmt::Display for $name {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let Self {
+                    $($field: $crate::__private::__message_binding!($field, $msg),)*
+                    source: $crate::__private::__message_binding!(source, $msg),
+                } = self;
+                ::core::write!(f, $msg)
+            }
+        }
+
+        impl ::core::error::Error for $name {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                use $crate::__private::AsDynError as _;
+                ::core::option::Option::Some(self.source.as_dyn_error())
+            }
+        }
+    };
+    (#[$doc:meta] $evis:vis $name:ident [] { $($vis:vis $field:ident: $ty:ty),* } => $msg:literal) => {
+        #[$doc]
+        #[derive(Debug)]
+        $evis struct $name {
+            $(
+                $vis $field: $ty,
+            )*
+        }
+
+        impl ::core::fmt::Display for $name {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let Self { $($field: $crate::__private::__message_binding!($field, $msg),)* } = self;
+                ::core::write!(f, $msg)
+            }
+        }
+
+        impl ::core::error::Error for $name {}
+    };
+}
+
+/// Defines the error struct for `error!`, deriving `thiserror::Error`
+#[cfg(all(feature = "atomic_error", feature = "thiserror"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __error_struct {
+    (#[$doc:meta] $evis:vis $name:ident [$source:ty] { $($vis:vis $field:ident: $ty:ty),* } => $msg:literal) => {
+        #[$doc]
+        #[derive(::thiserror::Error, Debug)]
         #[error($msg)]
         $evis struct $name {
             $(
@@ -19,8 +158,9 @@ macro_rules! error {
             pub source: $source,
         }
     };
-    ($evis:vis $name:ident { $($vis:vis $field:ident: $ty:ty),* $(,)? } => $msg:literal) => {
-        #[derive(thiserror::Error, Debug)]
+    (#[$doc:meta] $evis:vis $name:ident [] { $($vis:vis $field:ident: $ty:ty),* } => $msg:literal) => {
+        #[$doc]
+        #[derive(::thiserror::Error, Debug)]
         #[error($msg)]
         $evis struct $name {
             $(
@@ -30,6 +170,56 @@ macro_rules! error {
     };
 }
 
+/// Items used by the code generated with macros, not a part of the public API
+#[cfg(feature = "atomic_error")]
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(not(feature = "thiserror"))]
+    use core::error::Error;
+
+    /// Backtrace of the errors with `backtrace` marker
+    #[cfg(feature = "std")]
+    pub use std::backtrace::{Backtrace, BacktraceStatus};
+
+    /// Bindings of the fields visible to the message of the error
+    #[cfg(not(feature = "thiserror"))]
+    pub use errorset::__message_binding;
+
+    /// Converts the source of the error into the trait object, including the trait objects themselves
+    #[cfg(not(feature = "thiserror"))]
+    pub trait AsDynError<'a> {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a);
+    }
+
+    #[cfg(not(feature = "thiserror"))]
+    impl<'a, T: Error + 'a> AsDynError<'a> for T {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    #[cfg(not(feature = "thiserror"))]
+    impl<'a> AsDynError<'a> for dyn Error + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    #[cfg(not(feature = "thiserror"))]
+    impl<'a> AsDynError<'a> for dyn Error + Send + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    #[cfg(not(feature = "thiserror"))]
+    impl<'a> AsDynError<'a> for dyn Error + Send + Sync + 'a {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+}
+
 macro_rules! ternary {
     ($condition: expr, $_true: expr, $_false: expr) => {
         if $condition {
@@ -101,6 +291,28 @@ where
     /// }
     /// ```
     fn raise<E, F: FnOnce(Self::T) -> E>(self, ok_to_err_map: F) -> Result<Self::ES, E>;
+
+    /// The same as `throw`, but `err_map` also receives the location of the call, so the error may point
+    /// to the line where it was detected.
+    ///
+    /// ```rust
+    /// use smarterr::Throwable;
+    /// fn example(val: i32) -> Result<i32, String> {
+    ///     val.throw_at(|v, location| format!("Value {} is not zero at {}", v, location))
+    /// }
+    /// ```
+    #[track_caller]
+    fn throw_at<E, F: FnOnce(Self::ES, &'static Location<'static>) -> E>(self, err_map: F) -> Result<Self::T, E> {
+        let location = Location::caller();
+        self.throw(|es| err_map(es, location))
+    }
+
+    /// The same as `raise`, but `ok_to_err_map` also receives the location of the call
+    #[track_caller]
+    fn raise_at<E, F: FnOnce(Self::T, &'static Location<'static>) -> E>(self, ok_to_err_map: F) -> Result<Self::ES, E> {
+        let location = Location::caller();
+        self.raise(|t| ok_to_err_map(t, location))
+    }
 }
 
 simple_throwable!((), |_| true);
@@ -120,9 +332,13 @@ simple_throwable!(u8, |&x| x == 0);
 simple_throwable!(i8, |&x| x == 0);
 
 simple_throwable!(&str, |s: &str| !s.is_empty());
+#[cfg(feature = "alloc")]
 simple_throwable!(String, |s: &String| !s.is_empty());
-simple_throwable!(Box<String>, |s: &Box<String>| !s.is_empty());
+#[cfg(feature = "alloc")]
+simple_throwable!(Box<String>, |s: &String| !s.is_empty());
+#[cfg(feature = "alloc")]
 simple_throwable!(Rc<String>, |s: &Rc<String>| !s.is_empty());
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
 simple_throwable!(Arc<String>, |s: &Arc<String>| !s.is_empty());
 
 impl<T> Throwable for Option<T> {
@@ -0,0 +1,199 @@
+//! Anonymous sum type of errors, like `OneOf<(InvalidData, OutOfBounds)>`, for error unions in generic code
+//! and in crates, which can not use proc macros.
+
+use core::{error::Error, fmt};
+
+/// Tuple of types, which may be held by [`OneOf`], like `(A, B, C)`
+pub trait TypeSet {
+    /// Enum with a variant for each type of the tuple
+    type Enum;
+}
+
+/// The type set contains the type `T` at the position `Index`. The position is inferred by the compiler,
+/// so it is never specified explicitly.
+pub trait Contains<T, Index>: TypeSet {
+    #[doc(hidden)]
+    fn inject(value: T) -> Self::Enum;
+    #[doc(hidden)]
+    fn project(value: Self::Enum) -> Result<T, Self::Enum>;
+}
+
+/// Every type of the set is contained in the set `U` at positions `Indices`
+pub trait SubsetOf<U: TypeSet, Indices>: TypeSet {
+    #[doc(hidden)]
+    fn broaden(value: Self::Enum) -> U::Enum;
+}
+
+/// Value of one of the types of the tuple `T`, like `OneOf<(InvalidData, OutOfBounds)>`.
+///
+/// `From` can not be implemented for each type of the tuple, since they may be the same type,
+/// so the value is created with [`OneOf::new`], like `.map_err(OneOf::new)?`
+/// or `value.throw(|v| OneOf::new(InvalidData { data: v }))`.
+///
+/// ```rust
+/// use smarterr::OneOf;
+/// use std::num::{ParseFloatError, ParseIntError};
+///
+/// fn parse(data: &str) -> Result<(u32, f32), OneOf<(ParseIntError, ParseFloatError)>> {
+///     let (int, float) = data.split_once(' ').unwrap_or_default();
+///     Ok((int.parse().map_err(OneOf::new)?, float.parse().map_err(OneOf::new)?))
+/// }
+///
+/// let error = parse("1 x").unwrap_err();
+/// assert_eq!(error.to_string(), "invalid float literal");
+/// assert!(error.narrow::<ParseIntError, _>().is_err());
+/// ```
+pub struct OneOf<T: TypeSet> {
+    value: T::Enum,
+}
+
+impl<T: TypeSet> OneOf<T> {
+    /// Creates the value of the type, which is one of the types of the tuple
+    pub fn new<E, Index>(value: E) -> Self
+    where
+        T: Contains<E, Index>,
+    {
+        OneOf { value: T::inject(value) }
+    }
+
+    /// Returns the value if it is of the type `E`, or the same `OneOf` otherwise
+    pub fn narrow<E, Index>(self) -> Result<E, Self>
+    where
+        T: Contains<E, Index>,
+    {
+        T::project(self.value).map_err(|value| OneOf { value })
+    }
+
+    /// Converts into `OneOf` of the wider set, containing all types of this set, like `OneOf<(A, B, C)>`
+    /// for `OneOf<(A, B)>`
+    pub fn broaden<U, Indices>(self) -> OneOf<U>
+    where
+        U: TypeSet,
+        T: SubsetOf<U, Indices>,
+    {
+        OneOf { value: T::broaden(self.value) }
+    }
+}
+
+impl<T: TypeSet> fmt::Debug for OneOf<T>
+where
+    T::Enum: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: TypeSet> fmt::Display for OneOf<T>
+where
+    T::Enum: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: TypeSet> Error for OneOf<T>
+where
+    T::Enum: Error,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.value.source()
+    }
+}
+
+/// Positions of the types in the set
+pub mod index {
+    macro_rules! indices {
+        ($($index:ident),*) => {
+            $(
+                #[doc = concat!("Position `", stringify!($index), "` of the type in the set")]
+                pub enum $index {}
+            )*
+        };
+    }
+
+    indices!(I0, I1, I2, I3, I4, I5, I6, I7);
+}
+
+use index::*;
+
+macro_rules! contains {
+    ($enum:ident, [$($all:ident),*], $index:ident, $ty:ident) => {
+        impl<$($all),*> Contains<$ty, $index> for ($($all,)*) {
+            fn inject(value: $ty) -> Self::Enum {
+                $enum::$index(value)
+            }
+
+            #[allow(unreachable_patterns)]
+            fn project(value: Self::Enum) -> Result<$ty, Self::Enum> {
+                match value {
+                    $enum::$index(value) => Ok(value),
+                    other => Err(other),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! type_set {
+    ($enum:ident $all:tt: $($index:ident $ty:ident $ty_index:ident),*) => {
+        /// Value of one of the types of the set, held by [`OneOf`]
+        #[doc(hidden)]
+        pub enum $enum<$($ty),*> {
+            $($index($ty),)*
+        }
+
+        impl<$($ty),*> TypeSet for ($($ty,)*) {
+            type Enum = $enum<$($ty),*>;
+        }
+
+        $(
+            contains!($enum, $all, $index, $ty);
+        )*
+
+        impl<$($ty,)* U: TypeSet, $($ty_index),*> SubsetOf<U, ($($ty_index,)*)> for ($($ty,)*)
+        where
+            $(U: Contains<$ty, $ty_index>,)*
+        {
+            fn broaden(value: Self::Enum) -> U::Enum {
+                match value {
+                    $($enum::$index(value) => <U as Contains<$ty, $ty_index>>::inject(value),)*
+                }
+            }
+        }
+
+        impl<$($ty: fmt::Debug),*> fmt::Debug for $enum<$($ty),*> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $($enum::$index(value) => value.fmt(f),)*
+                }
+            }
+        }
+
+        impl<$($ty: fmt::Display),*> fmt::Display for $enum<$($ty),*> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $($enum::$index(value) => value.fmt(f),)*
+                }
+            }
+        }
+
+        impl<$($ty: Error),*> Error for $enum<$($ty),*> {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                match self {
+                    $($enum::$index(value) => value.source(),)*
+                }
+            }
+        }
+    };
+}
+
+type_set!(Sum1 [A]: I0 A IA);
+type_set!(Sum2 [A, B]: I0 A IA, I1 B IB);
+type_set!(Sum3 [A, B, C]: I0 A IA, I1 B IB, I2 C IC);
+type_set!(Sum4 [A, B, C, D]: I0 A IA, I1 B IB, I2 C IC, I3 D ID);
+type_set!(Sum5 [A, B, C, D, E]: I0 A IA, I1 B IB, I2 C IC, I3 D ID, I4 E IE);
+type_set!(Sum6 [A, B, C, D, E, F]: I0 A IA, I1 B IB, I2 C IC, I3 D ID, I4 E IE, I5 F IF);
+type_set!(Sum7 [A, B, C, D, E, F, G]: I0 A IA, I1 B IB, I2 C IC, I3 D ID, I4 E IE, I5 F IF, I6 G IG);
+type_set!(Sum8 [A, B, C, D, E, F, G, H]: I0 A IA, I1 B IB, I2 C IC, I3 D ID, I4 E IE, I5 F IF, I6 G IG, I7 H IH);
@@ -0,0 +1,204 @@
+//! Human-readable report of the error and its sources, like `fn main() -> Result<(), Report>`
+
+use crate::{chain::Chain, context::WithContext};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{error::Error, fmt, panic::Location};
+#[cfg(feature = "std")]
+use std::backtrace::{Backtrace, BacktraceStatus};
+
+/// Layout of the sources of the error in the report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Sources are listed under `Caused by:` with their numbers
+    #[default]
+    Numbered,
+    /// Each source is nested under the previous one
+    Tree,
+}
+
+/// Report of the error, rendering its sources along with the context, the location where the report was created
+/// and the backtrace captured at the same time. `Debug` renders the report as well, so `main` returning
+/// `Result<(), Report>` prints it on failure.
+///
+/// The error is written with `{:#}`, so sets generated with `#[errorset(trace)]` list frames of their trace.
+/// The location and the backtrace sections describe the creation of the report, not the `location` field
+/// or the backtrace of the error itself.
+///
+/// ```rust
+/// use smarterr::{Report, ResultExt};
+///
+/// let error = "x".parse::<u32>().context("loading config").unwrap_err();
+/// let report = Report::from(error).show_location(false).show_backtrace(false);
+/// assert_eq!(
+///     report.to_string(),
+///     "Error: invalid digit found in string\n\nContext:\n    0: loading config"
+/// );
+/// ```
+pub struct Report {
+    error: Box<dyn Error + Send + Sync + 'static>,
+    /// Contexts in the order they were added, so the latest one is the last
+    contexts: Vec<String>,
+    location: &'static Location<'static>,
+    #[cfg(feature = "std")]
+    backtrace: Backtrace,
+    style: Style,
+    colour: bool,
+    show_context: bool,
+    show_location: bool,
+    #[cfg(feature = "std")]
+    show_backtrace: bool,
+}
+
+impl Report {
+    /// Creates the report, recording the location of the call and capturing the backtrace,
+    /// if it's enabled with `RUST_BACKTRACE`
+    #[track_caller]
+    pub fn new<E: Error + Send + Sync + 'static>(error: E) -> Self {
+        Report::from_boxed(Box::new(error))
+    }
+
+    #[track_caller]
+    pub fn from_boxed(error: Box<dyn Error + Send + Sync + 'static>) -> Self {
+        Report {
+            error,
+            contexts: Vec::new(),
+            location: Location::caller(),
+            #[cfg(feature = "std")]
+            backtrace: Backtrace::capture(),
+            style: Style::default(),
+            colour: false,
+            show_context: true,
+            show_location: true,
+            #[cfg(feature = "std")]
+            show_backtrace: true,
+        }
+    }
+
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// Returns the location, where the report was created, which is shown as `Reported at:`
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Sets the layout of the sources, `Style::Numbered` by default
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Highlights the message and section headers with ANSI colours, disabled by default
+    pub fn colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    /// Shows contexts of the error converted from `WithContext`, enabled by default
+    pub fn show_context(mut self, show: bool) -> Self {
+        self.show_context = show;
+        self
+    }
+
+    /// Shows the location, where the report was created, enabled by default
+    pub fn show_location(mut self, show: bool) -> Self {
+        self.show_location = show;
+        self
+    }
+
+    /// Shows the backtrace captured when the report was created, if it's enabled, enabled by default
+    #[cfg(feature = "std")]
+    pub fn show_backtrace(mut self, show: bool) -> Self {
+        self.show_backtrace = show;
+        self
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for Report {
+    #[track_caller]
+    fn from(error: E) -> Self {
+        Report::new(error)
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> From<WithContext<E>> for Report {
+    #[track_caller]
+    fn from(error: WithContext<E>) -> Self {
+        let mut contexts = error.contexts().map(String::from).collect::<Vec<_>>();
+        contexts.reverse();
+        Report { contexts, ..Report::new(error.into_error()) }
+    }
+}
+
+/// Writes the text, indenting its lines after the first one
+fn write_indented(f: &mut fmt::Formatter<'_>, text: &dyn fmt::Display, indent: &str) -> fmt::Result {
+    let text = alloc::format!("{text}");
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            write!(f, "\n{indent}")?;
+        }
+        f.write_str(line)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (bold, red, reset) = if self.colour {
+            ("\x1b[1m", "\x1b[1;31m", "\x1b[0m")
+        } else {
+            ("", "", "")
+        };
+
+        write!(f, "{red}Error:{reset} ")?;
+        // the alternate form adds the trace of the traced sets
+        write_indented(f, &format_args!("{:#}", self.error), "       ")?;
+
+        let sources = Chain::new(&*self.error).skip(1);
+        match self.style {
+            Style::Numbered => {
+                for (i, source) in sources.enumerate() {
+                    if i == 0 {
+                        write!(f, "\n\n{bold}Caused by:{reset}")?;
+                    }
+                    write!(f, "\n    {i}: ")?;
+                    write_indented(f, &source, "       ")?;
+                }
+            }
+            Style::Tree => {
+                for (depth, source) in sources.enumerate() {
+                    let indent = "    ".repeat(depth);
+                    write!(f, "\n{indent}└── ")?;
+                    write_indented(f, &source, &alloc::format!("{indent}    "))?;
+                }
+            }
+        }
+
+        if self.show_context && !self.contexts.is_empty() {
+            write!(f, "\n\n{bold}Context:{reset}")?;
+            for (i, context) in self.contexts.iter().rev().enumerate() {
+                write!(f, "\n    {i}: ")?;
+                write_indented(f, context, "       ")?;
+            }
+        }
+
+        if self.show_location {
+            write!(f, "\n\n{bold}Reported at:{reset}\n    {}", self.location)?;
+        }
+
+        #[cfg(feature = "std")]
+        if self.show_backtrace && self.backtrace.status() == BacktraceStatus::Captured {
+            write!(f, "\n\n{bold}Report backtrace:{reset}\n{}", self.backtrace)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the report, so it's printed by `main` returning `Result<(), Report>`
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,88 @@
+//! Trace of the error, recorded as it propagates through functions with `#[errorset(trace)]`
+
+use alloc::vec::Vec;
+use core::{fmt, panic::Location};
+
+/// Function the error passed through, along with the location, where it was converted into the error set
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+    function: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl Frame {
+    pub fn new(function: &'static str, location: &'static Location<'static>) -> Self {
+        Frame { function, location }
+    }
+
+    /// Name of the function, like `parse` or `Parser::parse` for methods
+    pub fn function(&self) -> &'static str {
+        self.function
+    }
+
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.function, self.location)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {} ({})", self.function, self.location)
+    }
+}
+
+/// Frames of the functions the error passed through, from the origin of the error to the latest function
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Trace {
+    frames: Vec<Frame>,
+}
+
+impl Trace {
+    pub fn push(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl fmt::Debug for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.frames).finish()
+    }
+}
+
+/// Writes frames on separate lines, starting from the latest one
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, frame) in self.frames.iter().rev().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "    {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Fallback for error types without the trace, used by the code generated for `#[errorset(trace)]`.
+/// Traced error sets define the inherent method of the same name, which takes their trace instead.
+#[doc(hidden)]
+pub trait NoTrace {
+    fn __take_trace(&mut self) -> Trace {
+        Trace::default()
+    }
+}
+
+impl<T> NoTrace for T {}
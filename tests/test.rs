@@ -19,12 +19,20 @@ mod atomic_error {
         }
     }
 
-    // define several atomic struct-based error types using the `thiserror` crate
+    // define several atomic struct-based error types using the `error!` macro
     error!(pub InvalidData<ParseIntError> { pub data: String } => "Invalid data provided [data={data}]");
     error!(pub OutOfBounds { pub value: u32, pub bound: Bounds } => "Value is out of bound [value={value}, bound={bound:?}]");
+    // errors with the `location` marker record where they were created
+    error!(pub InvalidPort<ParseIntError> [location] { pub data: String } => "Invalid port [data={data}]");
+    error!(pub ReservedPort [location] { pub port: u16 } => "Port {port} is reserved");
+    // errors with the `backtrace` marker capture the backtrace when they are created
+    error!(pub ClosedPort [location, backtrace] { pub port: u16 } => "Port {port} is closed");
+    // the message may refer to the source and the fields of the markers
+    error!(pub InvalidLimit<ParseIntError> [location] { pub data: String } => "Invalid limit {data:?} at {location}: {source}");
 }
 
 use atomic_error::*;
+use smarterr::OneOf;
 
 struct _SomeStruct {}
 
@@ -42,6 +50,48 @@ impl _SomeStruct {
     }
 }
 
+fn parse_bounded(data: &str) -> Result<u32, OneOf<(InvalidData, OutOfBounds)>> {
+    let max = Bounds::Upper(1000);
+    let value = data
+        .parse::<u32>()
+        .throw(|source| OneOf::new(InvalidData { data: data.to_owned(), source }))?;
+    max.test(value)
+        .throw(|_| OneOf::new(OutOfBounds { value, bound: max }))?;
+    Ok(value)
+}
+
+#[errorset(trace)]
+fn read_value(data: &str) -> Result<u32, (InvalidData, OutOfBounds)> {
+    let max = Bounds::Upper(1000);
+    let value = data
+        .parse::<u32>()
+        .throw(|source| InvalidData { data: data.to_owned(), source })?;
+    max.test(value).throw(|_| OutOfBounds { value, bound: max })?;
+    Ok(value)
+}
+
+#[errorset(trace)]
+fn read_sum(left: &str, right: &str) -> Result<u32, (ReadValueErrors,)> {
+    Ok(read_value(left)? + read_value(right)?)
+}
+
+fn parse_port(data: &str) -> Result<u16, OneOf<(InvalidPort, ReservedPort)>> {
+    let port = data
+        .parse::<u16>()
+        .throw(|source| OneOf::new(InvalidPort::new(data.to_owned(), source)))?;
+    (port >= 1024).throw_at(|_, location| OneOf::new(ReservedPort { port, location }))?;
+    Ok(port)
+}
+
+#[errorset(trace, backtrace)]
+fn open_port(data: &str) -> Result<u16, (InvalidPort, ClosedPort)> {
+    let port = data
+        .parse::<u16>()
+        .throw(|source| InvalidPort::new(data.to_owned(), source))?;
+    (port % 2 == 0).throw(|_| ClosedPort::new(port))?;
+    Ok(port)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +122,209 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_one_of() {
+        assert_eq!(parse_bounded("10").unwrap(), 10);
+
+        let e = parse_bounded("x1").unwrap_err();
+        assert_eq!(
+            format!("{:#}", anyhow!(e)),
+            "Invalid data provided [data=x1]: invalid digit found in string"
+        );
+
+        let e = parse_bounded("10000").unwrap_err();
+        let e = match e.narrow::<InvalidData, _>() {
+            Ok(_) => panic!("Expected OutOfBounds"),
+            Err(e) => e,
+        };
+        let e: OneOf<(std::io::Error, OutOfBounds, InvalidData)> = e.broaden();
+        let e = e.narrow::<OutOfBounds, _>().unwrap();
+        assert_eq!(e.value, 10000);
+    }
+
+    #[test]
+    fn test_trace() {
+        assert_eq!(read_sum("1", "2").unwrap(), 3);
+
+        let e = read_sum("1", "x2").unwrap_err();
+        let functions = e
+            .trace()
+            .frames()
+            .iter()
+            .map(|frame| frame.function())
+            .collect::<Vec<_>>();
+        assert_eq!(functions, ["read_value", "read_sum"]);
+        assert!(matches!(e.kind(), ReadSumErrorsKind::ReadValueErrors(_)));
+        assert_eq!(e.to_string(), "Invalid data provided [data=x2]");
+
+        let report = format!("{e:#}");
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Invalid data provided [data=x2]");
+        assert!(lines[1].starts_with("    at read_sum (tests/test.rs:"));
+        assert!(lines[2].starts_with("    at read_value (tests/test.rs:"));
+    }
+
+    #[test]
+    fn test_location() {
+        assert_eq!(parse_port("8080").unwrap(), 8080);
+
+        // the line of `parse_port`, where the error is created
+        let line_of = |code: &str| {
+            include_str!("test.rs")
+                .lines()
+                .position(|line| line.contains(code))
+                .unwrap()
+                + 1
+        };
+
+        let e = parse_port("x").unwrap_err().narrow::<InvalidPort, _>().unwrap();
+        assert_eq!(e.location.file(), file!());
+        assert_eq!(
+            e.location.line() as usize,
+            line_of(".throw(|source| OneOf::new(InvalidPort::new(")
+        );
+        assert_eq!(e.to_string(), "Invalid port [data=x]");
+
+        let e = parse_port("80").unwrap_err().narrow::<ReservedPort, _>().unwrap();
+        assert_eq!(e.location.file(), file!());
+        assert_eq!(e.location.line() as usize, line_of("(port >= 1024).throw_at("));
+    }
+
+    #[test]
+    fn test_message_fields() {
+        let e = InvalidLimit::new("x".to_owned(), "x".parse::<u32>().unwrap_err());
+        assert_eq!(
+            e.to_string(),
+            format!("Invalid limit \"x\" at {}: invalid digit found in string", e.location)
+        );
+    }
+
+    #[test]
+    fn test_backtrace() {
+        use std::backtrace::BacktraceStatus;
+
+        assert_eq!(open_port("8080").unwrap(), 8080);
+        // the backtrace is returned only if it was captured, depending on `RUST_BACKTRACE`
+        let e = open_port("8081").unwrap_err();
+        let OpenPortErrorsKind::ClosedPort(closed) = e.kind() else {
+            panic!("Expected ClosedPort");
+        };
+        let captured = closed.backtrace.status() == BacktraceStatus::Captured;
+        assert_eq!(e.backtrace().is_some(), captured);
+        assert!(open_port("x").unwrap_err().backtrace().is_none());
+    }
+
+    #[test]
+    fn test_context() {
+        use smarterr::{ResultExt, WithContext};
+        use std::error::Error;
+
+        let load = |data: &str| -> Result<u32, WithContext<eei::ParseU32Errors>> {
+            let value = _SomeStruct {}
+                .parse_u32(data)
+                .with_context(|| format!("parsing {data}"))?;
+            Ok(value)
+        };
+        assert_eq!(load("100").unwrap(), 100);
+
+        let e = load("x1").context("loading config").unwrap_err();
+        assert!(matches!(*e, eei::ParseU32Errors::InvalidData(_)));
+        assert_eq!(e.contexts().collect::<Vec<_>>(), ["loading config", "parsing x1"]);
+        assert_eq!(e.to_string(), "loading config");
+        assert_eq!(
+            format!("{e:#}"),
+            "loading config: parsing x1: Invalid data provided [data=x1]: invalid digit found in string"
+        );
+
+        let e: Box<dyn Error + Send + Sync> = e.into();
+        assert_eq!(e.to_string(), "loading config: parsing x1");
+        assert_eq!(e.source().unwrap().to_string(), "Invalid data provided [data=x1]");
+    }
+
+    #[test]
+    fn test_chain() {
+        use smarterr::{ErrorExt, ResultExt};
+        use std::{error::Error, num::ParseIntError};
+
+        let e = _SomeStruct {}.parse_u32("x1").unwrap_err();
+        let messages = e.chain().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            ["Invalid data provided [data=x1]", "invalid digit found in string"]
+        );
+        assert!(e.find_source::<ParseIntError>().is_some());
+        assert!(e.root_cause().is::<ParseIntError>());
+        assert!(e.downcast_chain_ref::<eei::ParseU32Errors>().is_some());
+        assert!(e.find_source::<eei::ParseU32Errors>().is_none());
+        // the error held by the variant is found as well, though it is not a source of the set
+        assert_eq!(e.find_source::<InvalidData>().unwrap().data, "x1");
+        assert_eq!(e.downcast_chain_ref::<InvalidData>().unwrap().data, "x1");
+        assert!(e.find_source::<OutOfBounds>().is_none());
+        // the trait object of the set looks at the sources of the held error only
+        let dyn_error: &(dyn Error + 'static) = &e;
+        assert!(dyn_error.find_source::<InvalidData>().is_none());
+        assert!(dyn_error.find_source::<ParseIntError>().is_some());
+
+        // nested and traced sets reach the error held by the innermost set
+        let e = read_sum("1", "x2").unwrap_err();
+        assert_eq!(e.find_source::<InvalidData>().unwrap().data, "x2");
+        assert!(e.downcast_chain_ref::<ParseIntError>().is_some());
+
+        let e = _SomeStruct {}.parse_u32("x1").context("loading config").unwrap_err();
+        assert!(e.find_source::<ParseIntError>().is_some());
+        let e: Box<dyn Error + Send + Sync> = e.into();
+        assert_eq!(e.chain().count(), 3);
+        assert!(e.find_source::<eei::ParseU32Errors>().is_some());
+        assert_eq!(e.root_cause().to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_report() {
+        use smarterr::{report::Style, Report, ResultExt};
+
+        let load = |data: &str| -> Result<u32, Report> {
+            let value = _SomeStruct {}.parse_u32(data).context("loading config")?;
+            Ok(value)
+        };
+        assert_eq!(load("100").unwrap(), 100);
+
+        let report = load("x1").unwrap_err().show_backtrace(false);
+        assert_eq!(report.location().file(), file!());
+
+        assert_eq!(
+            format!("{report:?}"),
+            format!(
+                "Error: Invalid data provided [data=x1]\n\n\
+                 Caused by:\n    0: invalid digit found in string\n\n\
+                 Context:\n    0: loading config\n\n\
+                 Reported at:\n    {}",
+                report.location()
+            )
+        );
+
+        let report = Report::new(InvalidData {
+            data: "x".to_owned(),
+            source: "x".parse::<u32>().unwrap_err(),
+        })
+        .style(Style::Tree)
+        .show_location(false)
+        .show_backtrace(false);
+        assert_eq!(
+            report.to_string(),
+            "Error: Invalid data provided [data=x]\n└── invalid digit found in string"
+        );
+        assert!(report.colour(true).to_string().starts_with("\x1b[1;31mError:\x1b[0m "));
+
+        // frames of the traced sets are rendered along with the message
+        let report = Report::new(read_sum("1", "x2").unwrap_err())
+            .show_location(false)
+            .show_backtrace(false)
+            .to_string();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Error: Invalid data provided [data=x2]");
+        assert!(lines[1].starts_with("           at read_sum (tests/test.rs:"));
+        assert!(lines[2].starts_with("           at read_value (tests/test.rs:"));
+        assert_eq!(lines[4], "Caused by:");
+    }
 }